@@ -1,8 +1,10 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
+use super::config::{ProposerMode, QuorumMode, VoteCurve};
+
 #[type_abi]
-#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, Clone)]
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, PartialEq, Clone, Debug)]
 pub enum BoardAction<M: ManagedTypeApi> {
     Nothing,
 
@@ -17,6 +19,62 @@ pub enum BoardAction<M: ManagedTypeApi> {
     RemoveVotingToken(TokenIdentifier<M>),
 
     ChangeTaxAmount(BigUint<M>),
+
+    ChangeExecutionDelay(u64),
+    ChangeGracePeriod(u64),
+    VetoProposal(u64),
+
+    ChangeProposalDeposit(BigUint<M>),
+    ChangeDepositRefundTurnout(BigUint<M>),
+    ChangeMaxOpenProposals(usize),
+
+    ChangeProposerMode(ProposerMode),
+    ChangeProposerThreshold(BigUint<M>),
+
+    ChangeVotingDelay(u64),
+
+    ChangeParticipationQuorum(BigUint<M>),
+    ChangeApprovalThreshold(u64),
+
+    SetLockMultiplier(u64, u64),
+
+    SetVotingTokenNonceWeight(TokenIdentifier<M>, u64, BigUint<M>),
+
+    ChangeReferendumQuorum(u64),
+
+    ChangeQuorumMode(QuorumMode),
+    SetVotingTokenSupply(TokenIdentifier<M>, BigUint<M>),
+
+    SetVoteCurve(TokenIdentifier<M>, VoteCurve, BigUint<M>),
+
+    ChangeVotingPeriodBounds(u64, u64),
+    SetUrgencyQuorumMultiplier(u64, u64),
+
+    ChangeOptimisticVetoThreshold(BigUint<M>),
+    SetOptimisticSpendingCap(EgldOrEsdtTokenIdentifier<M>, BigUint<M>),
+
+    SetBudgetCap(ManagedBuffer<M>, EgldOrEsdtTokenIdentifier<M>, BigUint<M>),
+    ChangeBudgetPeriod(u64),
+
+    SetMinReserve(EgldOrEsdtTokenIdentifier<M>, u64, BigUint<M>),
+    ChangeReserveBoardQuorum(usize),
+
+    ChangeBindingReferendums(bool),
+}
+
+impl<M: ManagedTypeApi> BoardAction<M> {
+    // actions changing the board itself, token holders can always propose them
+    pub fn changes_board(&self) -> bool {
+        matches!(
+            self,
+            BoardAction::AddBoardMember(_) | BoardAction::RemoveBoardMember(_) | BoardAction::ChangeBoardQuorum(_)
+        )
+    }
+
+    // actions guarding the treasury reserve, only the board can perform them, with the reserve quorum
+    pub fn is_reserve_action(&self) -> bool {
+        matches!(self, BoardAction::SetMinReserve(_, _, _) | BoardAction::ChangeReserveBoardQuorum(_))
+    }
 }
 
 #[multiversx_sc::module]
@@ -46,10 +104,31 @@ pub trait BoardConfigModule {
     #[storage_mapper("board_quorum")]
     fn board_quorum(&self) -> SingleValueMapper<usize>;
 
+    // signers needed for reserve actions, zero means the whole board
+    #[view(getReserveBoardQuorum)]
+    #[storage_mapper("reserve_board_quorum")]
+    fn reserve_board_quorum(&self) -> SingleValueMapper<usize>;
+
     // views
     #[view(quorumReached)]
     fn quorum_reached(&self, action_id: usize) -> bool {
-        self.get_action_valid_signer_count(action_id) >= self.board_quorum().get()
+        self.get_action_valid_signer_count(action_id) >= self.get_action_required_signers(action_id)
+    }
+
+    #[view(getActionRequiredSigners)]
+    fn get_action_required_signers(&self, action_id: usize) -> usize {
+        let board_quorum = self.board_quorum().get();
+        if !self.action_mapper().get(action_id).is_reserve_action() {
+            return board_quorum;
+        }
+
+        // board changes cannot shrink the board below the reserve quorum, so it is never clamped
+        let reserve_quorum = match self.reserve_board_quorum().get() {
+            0 => self.board_members().len(),
+            reserve_quorum => reserve_quorum,
+        };
+
+        core::cmp::max(reserve_quorum, board_quorum)
     }
 
     #[view]
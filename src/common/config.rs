@@ -1,8 +1,10 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
-use crate::common::errors::*;
-use super::board_config;
+use core::borrow::Borrow;
+
+use crate::common::{consts::{MAX_PERCENTAGE, ONE}, errors::*};
+use super::board_config::{self, BoardAction};
 
 #[type_abi]
 #[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
@@ -16,12 +18,31 @@ pub enum State {
 pub struct Action<M: ManagedTypeApi> {
     pub gas_limit: u64,
     pub dest_address: ManagedAddress<M>,
-    pub payment_token: EgldOrEsdtTokenIdentifier<M>,
-    pub payment_amount: BigUint<M>,
+    pub payments: ManagedVec<M, EgldOrEsdtTokenPayment<M>>,
     pub endpoint_name: ManagedBuffer<M>,
     pub arguments: ManagedVec<M, ManagedBuffer<M>>,
 }
 
+impl<M: ManagedTypeApi> Action<M> {
+    // EGLD can only be sent alone, everything else goes out as a multi-transfer
+    pub fn get_payment(&self) -> EgldOrMultiEsdtPayment<M> {
+        let mut esdt_payments = ManagedVec::new();
+        for payment in self.payments.iter() {
+            if payment.token_identifier.is_egld() {
+                return EgldOrMultiEsdtPayment::Egld(payment.amount.clone());
+            }
+
+            esdt_payments.push(EsdtTokenPayment::new(
+                payment.token_identifier.clone().unwrap_esdt(),
+                payment.token_nonce,
+                payment.amount.clone(),
+            ));
+        }
+
+        EgldOrMultiEsdtPayment::MultiEsdt(esdt_payments)
+    }
+}
+
 #[type_abi]
 #[derive(TopEncode, TopDecode)]
 pub struct ProposalCreationArgs<M: ManagedTypeApi> {
@@ -34,6 +55,20 @@ pub struct ProposalCreationArgs<M: ManagedTypeApi> {
 pub enum VoteType {
     Upvote = 1,
     DownVote = 2,
+    Abstain = 3,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug)]
+pub struct Vote<M: ManagedTypeApi> {
+    pub vote_type: VoteType,
+    pub weight: BigUint<M>,
+    // weighted tokens before the curve and caps, what percentage quorums are measured in
+    pub raw_weight: BigUint<M>,
+    // set when the vote was cast by the voter's delegate
+    pub delegate: Option<ManagedAddress<M>>,
+    // chosen option of a poll
+    pub option: Option<usize>,
 }
 
 #[type_abi]
@@ -44,6 +79,11 @@ pub enum ProposalStatus {
     Defeated,
     Succeeded,
     Executed,
+    Queued,
+    Vetoed,
+    Expired,
+    ExecutionFailed,
+    Cancelled,
 }
 
 #[type_abi]
@@ -52,6 +92,9 @@ pub enum ProposalTypeEnum {
     Nothing,
 
     NewTransfer,
+    BoardAction,
+    Poll,
+    OptimisticTransfer,
 }
 
 #[type_abi]
@@ -60,6 +103,192 @@ pub enum ProposalType<M: ManagedTypeApi> {
     Nothing,
 
     NewTransfer(TransferProposal<M>),
+    BoardAction(BoardAction<M>),
+    // non-binding multiple choice, the result is only recorded
+    Poll(ManagedVec<M, ManagedBuffer<M>>),
+    // passes after voting unless the downvotes reach the veto threshold
+    OptimisticTransfer(TransferProposal<M>),
+}
+
+impl<M: ManagedTypeApi> ProposalType<M> {
+    pub fn get_type(&self) -> ProposalTypeEnum {
+        match self {
+            ProposalType::Nothing => ProposalTypeEnum::Nothing,
+            ProposalType::NewTransfer(_) => ProposalTypeEnum::NewTransfer,
+            ProposalType::BoardAction(_) => ProposalTypeEnum::BoardAction,
+            ProposalType::Poll(_) => ProposalTypeEnum::Poll,
+            ProposalType::OptimisticTransfer(_) => ProposalTypeEnum::OptimisticTransfer,
+        }
+    }
+
+    pub fn new_option_tallies(&self) -> ManagedVec<M, BigUint<M>> {
+        let mut option_tallies = ManagedVec::new();
+        if let ProposalType::Poll(options) = self {
+            for _ in 0..options.len() {
+                option_tallies.push(BigUint::zero());
+            }
+        }
+
+        option_tallies
+    }
+
+    pub fn get_transfer_proposal(&self) -> Option<&TransferProposal<M>> {
+        match self {
+            ProposalType::NewTransfer(transfer_proposal) |
+            ProposalType::OptimisticTransfer(transfer_proposal) => Some(transfer_proposal),
+            _ => None,
+        }
+    }
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug, ManagedVecItem)]
+pub enum VoteCurve {
+    Sqrt,
+    Linear,
+    // linear up to a cap per voter
+    CappedLinear,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug, ManagedVecItem)]
+pub struct VotingTokenWeight<M: ManagedTypeApi> {
+    pub token: TokenIdentifier<M>,
+    // 0 is the weight of the whole collection
+    pub nonce: u64,
+    pub weight: BigUint<M>,
+    pub curve: VoteCurve,
+    // only used by CappedLinear, in weighted tokens
+    pub cap: BigUint<M>,
+}
+
+// a nonce entry takes precedence over the collection entry
+pub fn find_voting_token<M: ManagedTypeApi>(
+    voting_tokens: &ManagedVec<M, VotingTokenWeight<M>>,
+    token: &TokenIdentifier<M>,
+    nonce: u64,
+) -> Option<VotingTokenWeight<M>> {
+    let mut collection_entry = None;
+    for voting_token in voting_tokens.iter() {
+        if &voting_token.token != token {
+            continue;
+        }
+
+        if voting_token.nonce == nonce {
+            return Some(voting_token.clone());
+        }
+        if voting_token.nonce == 0 {
+            collection_entry = Some(voting_token.clone());
+        }
+    }
+
+    collection_entry
+}
+
+// payments that add up when their token and nonce match
+pub trait MergeablePayment<M: ManagedTypeApi>: ManagedVecItem + Clone {
+    fn is_same_token(&self, other: &Self) -> bool;
+
+    fn add_amount(&mut self, other: &Self);
+}
+
+impl<M: ManagedTypeApi> MergeablePayment<M> for EsdtTokenPayment<M> {
+    fn is_same_token(&self, other: &Self) -> bool {
+        self.token_identifier == other.token_identifier && self.token_nonce == other.token_nonce
+    }
+
+    fn add_amount(&mut self, other: &Self) {
+        self.amount += &other.amount;
+    }
+}
+
+impl<M: ManagedTypeApi> MergeablePayment<M> for EgldOrEsdtTokenPayment<M> {
+    fn is_same_token(&self, other: &Self) -> bool {
+        self.token_identifier == other.token_identifier && self.token_nonce == other.token_nonce
+    }
+
+    fn add_amount(&mut self, other: &Self) {
+        self.amount += &other.amount;
+    }
+}
+
+// adds the payment to the entry of the same token and nonce, or appends it
+pub fn merge_payment<M: ManagedTypeApi, P: MergeablePayment<M>>(payments: &mut ManagedVec<M, P>, payment: P) {
+    let mut merged = ManagedVec::new();
+    let mut found = false;
+    for old_payment in payments.iter() {
+        let mut old_payment = P::clone(old_payment.borrow());
+        if old_payment.is_same_token(&payment) {
+            old_payment.add_amount(&payment);
+            found = true;
+        }
+        merged.push(old_payment);
+    }
+    if !found {
+        merged.push(payment);
+    }
+
+    *payments = merged;
+}
+
+// sqrt tokens share a single square root so splitting deposits gains nothing, linear and capped ones add up
+pub fn compute_vote_weight<M: ManagedTypeApi>(
+    voting_tokens: &ManagedVec<M, VotingTokenWeight<M>>,
+    payments: &ManagedVec<M, EsdtTokenPayment<M>>,
+) -> BigUint<M> {
+    let mut sqrt_total = BigUint::zero();
+    let mut linear_total = BigUint::zero();
+    for payment in payments.iter() {
+        if let Some(voting_token) = find_voting_token(voting_tokens, &payment.token_identifier, payment.token_nonce) {
+            let weight = &payment.amount * &voting_token.weight;
+            match voting_token.curve {
+                VoteCurve::Sqrt => sqrt_total += weight,
+                VoteCurve::Linear => linear_total += weight,
+                VoteCurve::CappedLinear => {},
+            }
+        }
+    }
+
+    // the cap covers all the nonces of a token
+    for voting_token in voting_tokens.iter() {
+        if voting_token.nonce != 0 || voting_token.curve != VoteCurve::CappedLinear {
+            continue;
+        }
+
+        let mut token_total = BigUint::zero();
+        for payment in payments.iter() {
+            if payment.token_identifier != voting_token.token {
+                continue;
+            }
+            if let Some(entry) = find_voting_token(voting_tokens, &payment.token_identifier, payment.token_nonce) {
+                token_total += &payment.amount * &entry.weight;
+            }
+        }
+
+        let cap = &voting_token.cap * ONE;
+        if token_total > cap {
+            linear_total += cap;
+        } else {
+            linear_total += token_total;
+        }
+    }
+
+    linear_total / ONE + (sqrt_total / ONE).sqrt()
+}
+
+// the weighted amount of the payments, with no curve or cap applied
+pub fn compute_raw_weight<M: ManagedTypeApi>(
+    voting_tokens: &ManagedVec<M, VotingTokenWeight<M>>,
+    payments: &ManagedVec<M, EsdtTokenPayment<M>>,
+) -> BigUint<M> {
+    let mut raw_total = BigUint::zero();
+    for payment in payments.iter() {
+        if let Some(voting_token) = find_voting_token(voting_tokens, &payment.token_identifier, payment.token_nonce) {
+            raw_total += &payment.amount * &voting_token.weight;
+        }
+    }
+
+    raw_total / ONE
 }
 
 #[type_abi]
@@ -74,14 +303,161 @@ pub struct Proposal<M: ManagedTypeApi> {
     pub description: ManagedBuffer<M>,
     pub status: ProposalStatus,
     pub was_executed: bool,
+    pub was_vetoed: bool,
+    pub was_cancelled: bool,
+    pub failed_action: Option<usize>,
     pub num_upvotes: BigUint<M>,
     pub num_downvotes: BigUint<M>,
+    pub num_abstains: BigUint<M>,
+    pub option_tallies: ManagedVec<M, BigUint<M>>,
+    pub raw_upvotes: BigUint<M>,
+    pub raw_downvotes: BigUint<M>,
+    pub raw_abstains: BigUint<M>,
+    // voting parameters captured at creation
+    pub voting_start: u64,
+    pub voting_end: u64,
+    pub quorum: BigUint<M>,
+    pub participation_quorum: BigUint<M>,
+    pub approval_threshold: u64,
+    pub veto_threshold: BigUint<M>,
+    // percentage modes resolve the quorums in raw weight
+    pub quorum_mode: QuorumMode,
+    pub voting_tokens: ManagedVec<M, VotingTokenWeight<M>>,
+    pub execution_delay: u64,
+    pub grace_period: u64,
+    pub voting_mode: VotingMode<M>,
+}
+
+impl<M: ManagedTypeApi> Proposal<M> {
+    pub fn get_participation(&self) -> BigUint<M> {
+        &self.num_upvotes + &self.num_downvotes + &self.num_abstains
+    }
+
+    pub fn get_raw_participation(&self) -> BigUint<M> {
+        &self.raw_upvotes + &self.raw_downvotes + &self.raw_abstains
+    }
+
+    // moves the tally of a vote type, and of the poll option if any, from the old weight to the new one
+    pub fn update_tally(
+        &mut self,
+        vote_type: &VoteType,
+        option: Option<usize>,
+        old_weight: &BigUint<M>,
+        new_weight: &BigUint<M>,
+    ) {
+        let tally = match vote_type {
+            VoteType::Upvote => &mut self.num_upvotes,
+            VoteType::DownVote => &mut self.num_downvotes,
+            VoteType::Abstain => &mut self.num_abstains,
+        };
+        *tally += new_weight;
+        *tally -= old_weight;
+
+        if let Some(option) = option {
+            let mut option_tallies = ManagedVec::new();
+            for (idx, option_tally) in self.option_tallies.iter().enumerate() {
+                if idx == option {
+                    option_tallies.push(&*option_tally + new_weight - old_weight);
+                } else {
+                    option_tallies.push(BigUint::clone(&option_tally));
+                }
+            }
+            self.option_tallies = option_tallies;
+        }
+    }
+
+    pub fn update_raw_tally(&mut self, vote_type: &VoteType, old_raw_weight: &BigUint<M>, new_raw_weight: &BigUint<M>) {
+        let raw_tally = match vote_type {
+            VoteType::Upvote => &mut self.raw_upvotes,
+            VoteType::DownVote => &mut self.raw_downvotes,
+            VoteType::Abstain => &mut self.raw_abstains,
+        };
+        *raw_tally += new_raw_weight;
+        *raw_tally -= old_raw_weight;
+    }
+
+    pub fn get_voting_token_weight(&self, token: &TokenIdentifier<M>, nonce: u64) -> Option<BigUint<M>> {
+        find_voting_token(&self.voting_tokens, token, nonce).map(|voting_token| voting_token.weight)
+    }
+
+    pub fn get_vote_weight(&self, payments: &ManagedVec<M, EsdtTokenPayment<M>>) -> BigUint<M> {
+        compute_vote_weight(&self.voting_tokens, payments)
+    }
+
+    pub fn get_raw_vote_weight(&self, payments: &ManagedVec<M, EsdtTokenPayment<M>>) -> BigUint<M> {
+        compute_raw_weight(&self.voting_tokens, payments)
+    }
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ExecutionMode {
+    // all actions run as synchronous calls and fail together
+    Atomic,
+    // each action runs as a promise and its outcome is recorded
+    PerAction,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug)]
+pub struct ProposalRevision<M: ManagedTypeApi> {
+    pub title: ManagedBuffer<M>,
+    pub description: ManagedBuffer<M>,
+    pub proposal_data: ProposalType<M>,
+    pub timestamp: u64,
 }
 
 #[type_abi]
 #[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug)]
 pub struct TransferProposal<M: ManagedTypeApi> {
     pub actions: ManagedVec<M, Action<M>>,
+    pub execution_mode: ExecutionMode,
+    // budget category the transfer is charged to, empty for transfers made before budgets
+    pub category: ManagedBuffer<M>,
+}
+
+impl<M: ManagedTypeApi> TransferProposal<M> {
+    pub fn get_payments(&self) -> ManagedVec<M, EgldOrEsdtTokenPayment<M>> {
+        let mut payments = ManagedVec::new();
+        for action in self.actions.iter() {
+            payments.append_vec(action.payments.clone());
+        }
+
+        payments
+    }
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum QuorumMode {
+    Absolute,
+    // quorums are basis points of the registered supply of the voting tokens
+    SupplyPercentage,
+    // quorums are basis points of the staked voting tokens
+    StakedPercentage,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ProposerMode {
+    BoardOnly,
+    TokenHolders,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug)]
+pub enum VotingMode<M: ManagedTypeApi> {
+    TokenWeighted,
+    // one vote per school identity linked with one of the relations
+    Referendum(ManagedVec<M, ManagedBuffer<M>>),
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug)]
+pub struct ProposalDeposit<M: ManagedTypeApi> {
+    pub payment: EsdtTokenPayment<M>,
+    // turnout that earns the deposit back even if the proposal is defeated
+    pub refund_turnout: BigUint<M>,
 }
 
 #[type_abi]
@@ -157,6 +533,55 @@ board_config::BoardConfigModule
     #[storage_mapper("voting_tokens")]
     fn voting_tokens(&self) -> MapMapper<TokenIdentifier, BigUint>;
 
+    // circulating supply of the voting tokens, used by the SupplyPercentage quorum mode
+    #[view(getVotingTokenSupply)]
+    #[storage_mapper("voting_token_supply")]
+    fn voting_token_supply(&self) -> MapMapper<TokenIdentifier, BigUint>;
+
+    // per nonce weights of NFT/SFT collections, overriding the collection weight
+    #[view(getVotingTokenNonceWeights)]
+    #[storage_mapper("voting_token_nonce_weights")]
+    fn voting_token_nonce_weights(&self, token: &TokenIdentifier) -> MapMapper<u64, BigUint>;
+
+    // weight curve of each voting token, Sqrt when not set
+    #[view(getVotingTokenCurves)]
+    #[storage_mapper("voting_token_curves")]
+    fn voting_token_curves(&self) -> MapMapper<TokenIdentifier, VoteCurve>;
+
+    #[view(getVotingTokenCaps)]
+    #[storage_mapper("voting_token_caps")]
+    fn voting_token_caps(&self) -> MapMapper<TokenIdentifier, BigUint>;
+
+    fn get_voting_tokens_snapshot(&self) -> ManagedVec<VotingTokenWeight<Self::Api>> {
+        let mut voting_tokens = ManagedVec::new();
+        for (token, weight) in self.voting_tokens().iter() {
+            let curve = self.voting_token_curves().get(&token).unwrap_or(VoteCurve::Sqrt);
+            let cap = self.voting_token_caps().get(&token).unwrap_or_default();
+            for (nonce, nonce_weight) in self.voting_token_nonce_weights(&token).iter() {
+                voting_tokens.push(VotingTokenWeight {
+                    token: token.clone(),
+                    nonce,
+                    weight: nonce_weight,
+                    curve,
+                    cap: cap.clone(),
+                });
+            }
+            voting_tokens.push(VotingTokenWeight { token, nonce: 0, weight, curve, cap });
+        }
+
+        voting_tokens
+    }
+
+    fn get_voting_token_weight(&self, token: &TokenIdentifier, nonce: u64) -> Option<BigUint> {
+        if nonce > 0 {
+            if let Some(weight) = self.voting_token_nonce_weights(token).get(&nonce) {
+                return Some(weight);
+            }
+        }
+
+        self.voting_tokens().get(token)
+    }
+
     // voting period (blocks)
     #[endpoint(setVotingPeriod)]
     fn set_voting_period(&self, period: u64) {
@@ -168,17 +593,121 @@ board_config::BoardConfigModule
     #[storage_mapper("voting_period")]
     fn voting_period(&self) -> SingleValueMapper<u64>;
 
-    // quorum
-    #[endpoint(setQuorum)]
-    fn set_quorum(&self, quorum: &BigUint) {
-        self.only_board_members();
-        self.quorum().set(quorum);
+    // bounds of the voting period a proposer can choose, custom periods are disabled while max is 0
+    #[view(getMinVotingPeriod)]
+    #[storage_mapper("min_voting_period")]
+    fn min_voting_period(&self) -> SingleValueMapper<u64>;
+
+    #[view(getMaxVotingPeriod)]
+    #[storage_mapper("max_voting_period")]
+    fn max_voting_period(&self) -> SingleValueMapper<u64>;
+
+    // voting period upper bound => quorum multiplier in basis points
+    #[view(getUrgencyQuorumMultipliers)]
+    #[storage_mapper("urgency_quorum_multipliers")]
+    fn urgency_quorum_multipliers(&self) -> MapMapper<u64, u64>;
+
+    // multiplier of the tightest bracket the period falls in, 1x when none
+    fn get_urgency_quorum_multiplier(&self, voting_period: u64) -> u64 {
+        let mut multiplier = MAX_PERCENTAGE;
+        let mut bracket = u64::MAX;
+        for (max_period, bracket_multiplier) in self.urgency_quorum_multipliers().iter() {
+            if voting_period <= max_period && max_period < bracket {
+                bracket = max_period;
+                multiplier = bracket_multiplier;
+            }
+        }
+
+        multiplier
     }
 
+    // time between creation and the start of voting, while the proposer can still edit or withdraw
+    #[view(getVotingDelay)]
+    #[storage_mapper("voting_delay")]
+    fn voting_delay(&self) -> SingleValueMapper<u64>;
+
+    // quorum (minimum margin of upvotes over downvotes)
     #[view(getQuorum)]
     #[storage_mapper("quorum")]
     fn quorum(&self) -> SingleValueMapper<BigUint>;
 
+    // how quorum and participation_quorum are read
+    #[view(getQuorumMode)]
+    #[storage_mapper("quorum_mode")]
+    fn quorum_mode(&self) -> SingleValueMapper<QuorumMode>;
+
+    // minimum total weight cast, abstentions included
+    #[view(getParticipationQuorum)]
+    #[storage_mapper("participation_quorum")]
+    fn participation_quorum(&self) -> SingleValueMapper<BigUint>;
+
+    // minimum share of upvotes among upvotes and downvotes, in basis points (0 = disabled)
+    #[view(getApprovalThreshold)]
+    #[storage_mapper("approval_threshold")]
+    fn approval_threshold(&self) -> SingleValueMapper<u64>;
+
+    // number of identities that must vote in a referendum, abstentions included (0 = referendums disabled)
+    #[view(getReferendumQuorum)]
+    #[storage_mapper("referendum_quorum")]
+    fn referendum_quorum(&self) -> SingleValueMapper<u64>;
+
+    // referendums are only signals and polls unless the board lets them carry transfers and board actions
+    #[view(getBindingReferendums)]
+    #[storage_mapper("binding_referendums")]
+    fn binding_referendums(&self) -> SingleValueMapper<bool>;
+
+    // downvotes that stop an optimistic proposal, read like the quorums (0 = optimistic proposals disabled)
+    #[view(getOptimisticVetoThreshold)]
+    #[storage_mapper("optimistic_veto_threshold")]
+    fn optimistic_veto_threshold(&self) -> SingleValueMapper<BigUint>;
+
+    // most an optimistic proposal can spend of each token, tokens without a cap cannot be spent
+    #[view(getOptimisticSpendingCaps)]
+    #[storage_mapper("optimistic_spending_caps")]
+    fn optimistic_spending_caps(&self) -> MapMapper<EgldOrEsdtTokenIdentifier, BigUint>;
+
+    // delay between the end of voting and execution, during which a proposal can be vetoed
+    #[view(getExecutionDelay)]
+    #[storage_mapper("execution_delay")]
+    fn execution_delay(&self) -> SingleValueMapper<u64>;
+
+    // window for executing a proposal once the delay passed (0 = no expiry)
+    #[view(getGracePeriod)]
+    #[storage_mapper("grace_period")]
+    fn grace_period(&self) -> SingleValueMapper<u64>;
+
+    // who can create proposals
+    #[view(getProposerMode)]
+    #[storage_mapper("proposer_mode")]
+    fn proposer_mode(&self) -> SingleValueMapper<ProposerMode>;
+
+    // weighted voting tokens needed to propose in TokenHolders mode
+    #[view(getProposerThreshold)]
+    #[storage_mapper("proposer_threshold")]
+    fn proposer_threshold(&self) -> SingleValueMapper<BigUint>;
+
+    // proposal deposit (governance token)
+    #[view(getProposalDepositAmount)]
+    #[storage_mapper("proposal_deposit_amount")]
+    fn proposal_deposit_amount(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getDepositRefundTurnout)]
+    #[storage_mapper("deposit_refund_turnout")]
+    fn deposit_refund_turnout(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getProposalDeposit)]
+    #[storage_mapper("proposal_deposits")]
+    fn proposal_deposits(&self, proposal_id: u64) -> SingleValueMapper<ProposalDeposit<Self::Api>>;
+
+    // open proposals per proposer (0 = unlimited)
+    #[view(getMaxOpenProposals)]
+    #[storage_mapper("max_open_proposals")]
+    fn max_open_proposals(&self) -> SingleValueMapper<usize>;
+
+    #[view(getProposerProposals)]
+    #[storage_mapper("proposer_proposals")]
+    fn proposer_proposals(&self, proposer: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
     // last proposal id
     #[view(getLastProposalId)]
     #[storage_mapper("last_proposal_id")]
@@ -189,11 +718,36 @@ board_config::BoardConfigModule
     #[storage_mapper("proposals")]
     fn proposals(&self, id: u64) -> SingleValueMapper<Proposal<Self::Api>>;
 
+    // previous versions of an edited proposal, oldest first
+    #[view(getProposalRevisions)]
+    #[storage_mapper("proposal_revisions")]
+    fn proposal_revisions(&self, proposal_id: u64) -> VecMapper<ProposalRevision<Self::Api>>;
+
+    // actions of a per-action proposal that already went through
+    #[view(getExecutedActions)]
+    #[storage_mapper("executed_actions")]
+    fn executed_actions(&self, proposal_id: u64) -> UnorderedSetMapper<usize>;
+
+    // actions of a proposal still waiting for their callback
+    #[view(getPendingActions)]
+    #[storage_mapper("pending_actions")]
+    fn pending_actions(&self, proposal_id: u64) -> SingleValueMapper<usize>;
+
     // voters amounts
     #[view(getVoterAmount)]
     #[storage_mapper("voters_amounts")]
     fn voters_amounts(&self, voter: &ManagedAddress, proposal_id: u64) -> SingleValueMapper<ManagedVec<EsdtTokenPayment>>;
 
+    // vote direction and weight counted for each voter
+    #[view(getVote)]
+    #[storage_mapper("votes")]
+    fn votes(&self, voter: &ManagedAddress, proposal_id: u64) -> SingleValueMapper<Vote<Self::Api>>;
+
+    // identities that voted in a referendum
+    #[view(getReferendumVoters)]
+    #[storage_mapper("referendum_voters")]
+    fn referendum_voters(&self, proposal_id: u64) -> UnorderedSetMapper<u64>;
+
     // proposal voters
     #[view(getProposalVoters)]
     #[storage_mapper("proposal_voters")]
@@ -266,30 +820,85 @@ board_config::BoardConfigModule
         if proposal.was_executed {
             return ProposalStatus::Executed;
         }
+        if proposal.was_vetoed {
+            return ProposalStatus::Vetoed;
+        }
+        if proposal.was_cancelled {
+            return ProposalStatus::Cancelled;
+        }
 
         let current_timestamp = self.blockchain().get_block_timestamp();
-        let proposal_timestamp = proposal.creation_timestamp;
-        let voting_period = self.voting_period().get();
-
-        let voting_start = proposal_timestamp;
-        let voting_end = voting_start + voting_period;
-
-        if current_timestamp < voting_start {
+        if current_timestamp < proposal.voting_start {
             return ProposalStatus::Pending;
         }
-        if current_timestamp >= voting_start && current_timestamp < voting_end {
+        if current_timestamp >= proposal.voting_start && current_timestamp < proposal.voting_end {
             return ProposalStatus::Active;
         }
+        if !self.is_proposal_approved(proposal) {
+            return ProposalStatus::Defeated;
+        }
+
+        let execution_start = proposal.voting_end + proposal.execution_delay;
+        if current_timestamp < execution_start {
+            return ProposalStatus::Queued;
+        }
+        if proposal.grace_period > 0 && current_timestamp >= execution_start + proposal.grace_period {
+            return ProposalStatus::Expired;
+        }
+        if proposal.failed_action.is_some() {
+            return ProposalStatus::ExecutionFailed;
+        }
+
+        ProposalStatus::Succeeded
+    }
+
+    fn is_proposal_approved(&self, proposal: &Proposal<Self::Api>) -> bool {
+        // percentage quorums are shares of the weighted tokens, so they are checked against the raw tallies
+        let (quorum_upvotes, quorum_downvotes, participation) = match proposal.quorum_mode {
+            QuorumMode::Absolute => (&proposal.num_upvotes, &proposal.num_downvotes, proposal.get_participation()),
+            _ => (&proposal.raw_upvotes, &proposal.raw_downvotes, proposal.get_raw_participation()),
+        };
+        if proposal.proposal_type == ProposalTypeEnum::OptimisticTransfer {
+            return quorum_downvotes < &proposal.veto_threshold;
+        }
+
+        if participation < proposal.participation_quorum {
+            return false;
+        }
 
         let total_upvotes = &proposal.num_upvotes;
         let total_downvotes = &proposal.num_downvotes;
-        let quorum = self.quorum().get();
-
-        if total_upvotes > total_downvotes && total_upvotes - total_downvotes >= quorum {
-            ProposalStatus::Succeeded
-        } else {
-            ProposalStatus::Defeated
+        if total_upvotes <= total_downvotes || quorum_upvotes < &(quorum_downvotes + &proposal.quorum) {
+            return false;
         }
+
+        proposal.approval_threshold == 0 ||
+            total_upvotes * MAX_PERCENTAGE >= (total_upvotes + total_downvotes) * proposal.approval_threshold
+    }
+
+    fn is_voting_closed(&self, status: &ProposalStatus) -> bool {
+        status != &ProposalStatus::Pending && status != &ProposalStatus::Active
+    }
+
+    // a proposal can be vetoed until it becomes executable
+    fn can_be_vetoed(&self, status: &ProposalStatus) -> bool {
+        status == &ProposalStatus::Pending || status == &ProposalStatus::Active || status == &ProposalStatus::Queued
+    }
+
+    // the board cannot stop the community from replacing it, only the main DAO vetoes those proposals
+    fn can_board_veto(&self, proposal: &Proposal<Self::Api>) -> bool {
+        !matches!(&proposal.proposal_data, ProposalType::BoardAction(action) if action.changes_board())
+    }
+
+    fn veto_proposal(&self, proposal_id: u64, by_main_dao: bool) {
+        require!(!self.proposals(proposal_id).is_empty(), ERROR_PROPOSAL_NOT_FOUND);
+
+        let mut proposal = self.proposals(proposal_id).get();
+        require!(self.can_be_vetoed(&self.get_proposal_status(&proposal)), ERROR_PROPOSAL_CANNOT_BE_VETOED);
+        require!(by_main_dao || self.can_board_veto(&proposal), ERROR_BOARD_CANNOT_VETO);
+
+        proposal.was_vetoed = true;
+        self.proposals(proposal_id).set(&proposal);
     }
 
     #[view(getContractInfo)]
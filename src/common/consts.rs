@@ -1,5 +1,11 @@
 pub const ONE: u64 = 1_000_000_000_000_000_000;
 
+pub const MAX_PERCENTAGE: u64 = 10_000;
+
+pub const STORAGE_VERSION: u8 = 3;
+
+pub const CALLBACK_GAS_LIMIT: u64 = 10_000_000;
+
 pub const CLASS_KEY: &[u8] = b"tfn_class";
 pub const MARK_KEY: &[u8] = b"tfn_mark";
 pub const ABSENCE_KEY: &[u8] = b"tfn_absence";
@@ -11,3 +17,5 @@ pub const JOB_KEY: &[u8] = b"tfn_job";
 pub const STUDENT_RELATION: &[u8] = b"tfn_student";
 pub const TEACHER_RELATION: &[u8] = b"tfn_teacher";
 pub const EMPLOYEE_RELATION: &[u8] = b"tfn_employee";
+
+pub const KEPT_DEPOSIT_MEMO: &[u8] = b"kept proposal deposit";
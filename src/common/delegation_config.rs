@@ -0,0 +1,38 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug)]
+pub struct Delegation<M: ManagedTypeApi> {
+    pub delegate: ManagedAddress<M>,
+    pub payments: ManagedVec<M, EsdtTokenPayment<M>>,
+    // end of the last voting period the delegated tokens were counted in
+    pub locked_until: u64,
+}
+
+#[multiversx_sc::module]
+pub trait DelegationConfigModule {
+    #[view(getDelegation)]
+    #[storage_mapper("delegations")]
+    fn delegations(&self, delegator: &ManagedAddress) -> SingleValueMapper<Delegation<Self::Api>>;
+
+    #[view(getDelegators)]
+    #[storage_mapper("delegators")]
+    fn delegators(&self, delegate: &ManagedAddress) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getDelegates)]
+    #[storage_mapper("delegates")]
+    fn delegates(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    // delegators a delegate accepts, every vote of the delegate loops over them; zero refuses delegations
+    #[view(getDelegationLimit)]
+    #[storage_mapper("delegation_limit")]
+    fn delegation_limit(&self, delegate: &ManagedAddress) -> SingleValueMapper<usize>;
+
+    fn remove_delegator(&self, delegate: &ManagedAddress, delegator: &ManagedAddress) {
+        self.delegators(delegate).swap_remove(delegator);
+        if self.delegators(delegate).is_empty() {
+            self.delegates().swap_remove(delegate);
+        }
+    }
+}
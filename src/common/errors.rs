@@ -3,11 +3,57 @@ pub static ERROR_QUORUM_NOT_SET: &[u8] = b"quorum not set";
 pub static ERROR_VOTING_PERIOD_NOT_SET: &[u8] = b"voting period not set";
 pub static ERROR_INVALID_PAYMENT: &[u8] = b"wrong payment token";
 pub static ERROR_ZERO_PAYMENT: &[u8] = b"zero payments are not allowed";
+pub static ERROR_EGLD_WITH_OTHER_PAYMENTS: &[u8] = b"egld cannot be sent together with other tokens";
 pub static ERROR_NOT_ENOUGH_FUNDS_TO_PROPOSE: &[u8] = b"not enough funds to create proposal";
+pub static ERROR_PROPOSER_THRESHOLD_NOT_MET: &[u8] = b"not enough voting power to propose";
+pub static ERROR_TOO_MANY_OPEN_PROPOSALS: &[u8] = b"too many open proposals";
+pub static ERROR_NO_DEPOSIT: &[u8] = b"no deposit to settle";
 pub static ERROR_PROPOSAL_NOT_FOUND: &[u8] = b"proposal does not exist";
+pub static ERROR_PROPOSAL_NOT_PENDING: &[u8] = b"proposal is not pending";
+pub static ERROR_PROPOSAL_TYPE_MISMATCH: &[u8] = b"proposal type cannot change";
+pub static ERROR_ONLY_PROPOSER: &[u8] = b"only proposer";
 pub static ERROR_PROPOSAL_NOT_ACTIVE: &[u8] = b"proposal is not active";
+pub static ERROR_VOTE_TYPE_MISMATCH: &[u8] = b"already voted with a different vote type";
+pub static ERROR_NO_VOTING_POWER: &[u8] = b"no voting power";
+pub static ERROR_CANNOT_DELEGATE_TO_SELF: &[u8] = b"cannot delegate to self";
+pub static ERROR_DELEGATION_LIMIT_REACHED: &[u8] = b"delegate does not accept more delegators";
+pub static ERROR_NO_DELEGATION: &[u8] = b"no delegation";
+pub static ERROR_DELEGATION_LOCKED: &[u8] = b"delegation is locked";
+pub static ERROR_INVALID_LOCK_DURATION: &[u8] = b"lock duration not allowed";
+pub static ERROR_NO_STAKE: &[u8] = b"no stake";
+pub static ERROR_STAKE_LOCKED: &[u8] = b"stake is locked";
+pub static ERROR_SHORTER_LOCK: &[u8] = b"lock cannot end before the current one";
+pub static ERROR_INVALID_RELATION: &[u8] = b"invalid relation";
+pub static ERROR_NOT_ELIGIBLE: &[u8] = b"not eligible to vote";
+pub static ERROR_IDENTITY_ALREADY_VOTED: &[u8] = b"identity already voted";
+pub static ERROR_REFERENDUM_PAYMENT: &[u8] = b"referendum votes take no payment";
+pub static ERROR_NOT_ENOUGH_OPTIONS: &[u8] = b"a poll needs at least two options";
+pub static ERROR_INVALID_OPTION: &[u8] = b"invalid option";
+pub static ERROR_POLL_VOTE_CHANGE: &[u8] = b"poll votes cannot change side";
+pub static ERROR_INVALID_VOTING_PERIOD: &[u8] = b"voting period out of bounds";
+pub static ERROR_OPTIMISTIC_DISABLED: &[u8] = b"optimistic proposals are disabled";
+pub static ERROR_OPTIMISTIC_REFERENDUM: &[u8] = b"optimistic proposals cannot be referendums";
+pub static ERROR_REFERENDUMS_DISABLED: &[u8] = b"referendums disabled";
+pub static ERROR_BINDING_REFERENDUMS_DISABLED: &[u8] = b"referendums can only be signals and polls";
+pub static ERROR_OPTIMISTIC_CAP_EXCEEDED: &[u8] = b"optimistic spending cap exceeded";
+pub static ERROR_NOT_ENOUGH_FREE_FUNDS: &[u8] = b"not enough free treasury funds";
+pub static ERROR_MISSING_BUDGET_CATEGORY: &[u8] = b"transfer must be tagged with a budget category";
+pub static ERROR_UNKNOWN_BUDGET_CATEGORY: &[u8] = b"unknown budget category";
+pub static ERROR_BUDGET_EXCEEDED: &[u8] = b"budget exceeded";
+pub static ERROR_MIN_RESERVE_BREACHED: &[u8] = b"transfer would breach the minimum reserve";
+pub static ERROR_BELOW_RESERVE_QUORUM: &[u8] = b"board cannot be smaller than the reserve quorum";
+pub static ERROR_BOARD_ONLY_ACTION: &[u8] = b"action can only be performed by the board";
+pub static ERROR_LEGACY_DEPOSIT: &[u8] = b"deposits made before the upgrade can only be redeemed";
+pub static ERROR_NO_VOTE: &[u8] = b"no vote on this proposal";
+pub static ERROR_SAME_VOTE_TYPE: &[u8] = b"vote type unchanged";
+pub static ERROR_NOT_ENOUGH_VOTED_AMOUNT: &[u8] = b"amount exceeds voted tokens";
 pub static ERROR_VOTING_PERIOD_NOT_ENDED: &[u8] = b"voting period not ended";
 pub static ERROR_PROPOSAL_NOT_SUCCEEDED: &[u8] = b"proposal not succeeded";
+pub static ERROR_PROPOSAL_CANNOT_BE_VETOED: &[u8] = b"proposal can no longer be vetoed";
+pub static ERROR_BOARD_CANNOT_VETO: &[u8] = b"board cannot veto proposals changing the board";
+pub static ERROR_EXECUTION_IN_PROGRESS: &[u8] = b"proposal execution in progress";
+pub static ERROR_ATOMIC_CROSS_SHARD: &[u8] = b"atomic execution requires destinations in the same shard";
+pub static ERROR_ONLY_MAIN_DAO: &[u8] = b"only main dao";
 pub static ERROR_STUDENT_NOT_FOUND: &[u8] = b"student not found";
 pub static ERROR_EMPLOYEE_NOT_FOUND: &[u8] = b"employee not found";
 pub static ERROR_CLASS_NOT_FOUND: &[u8] = b"class not found";
@@ -18,6 +64,10 @@ pub static ERROR_NOTHING_TO_REDEEM: &[u8] = b"nothing to redeem";
 pub static ERROR_ALREADY_BOARD_MEMBER: &[u8] = b"already board member";
 pub static ERROR_NOT_BOARD_MEMBER: &[u8] = b"not board member";
 pub static ERROR_ZERO_VALUE: &[u8] = b"value cannot be zero";
+pub static ERROR_INVALID_PERCENTAGE: &[u8] = b"invalid percentage";
+pub static ERROR_MISSING_TOKEN_SUPPLY: &[u8] = b"voting token supply not set";
+pub static ERROR_ZERO_RESOLVED_QUORUM: &[u8] = b"quorum resolves to zero";
+pub static ERROR_ZERO_CAP: &[u8] = b"capped curve needs a cap";
 pub static ERROR_TOKEN_ALREADY_EXISTS: &[u8] = b"token already exists";
 pub static ERROR_TOKEN_NOT_FOUND: &[u8] = b"token not found";
 pub static ERROR_IDENTITY_NOT_REGISTERED: &[u8] = b"identity not registered";
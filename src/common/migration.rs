@@ -0,0 +1,198 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::common::consts::{STORAGE_VERSION, STUDENT_RELATION};
+use super::{board_config, config::{self, *}, delegation_config, school_config, staking_config, treasury_config};
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
+pub struct LegacyAction<M: ManagedTypeApi> {
+    pub gas_limit: u64,
+    pub dest_address: ManagedAddress<M>,
+    pub payment_token: EgldOrEsdtTokenIdentifier<M>,
+    pub payment_amount: BigUint<M>,
+    pub endpoint_name: ManagedBuffer<M>,
+    pub arguments: ManagedVec<M, ManagedBuffer<M>>,
+}
+
+impl<M: ManagedTypeApi> LegacyAction<M> {
+    pub fn into_action(self) -> Action<M> {
+        let mut payments = ManagedVec::new();
+        if self.payment_amount > 0 {
+            payments.push(EgldOrEsdtTokenPayment::new(self.payment_token, 0, self.payment_amount));
+        }
+
+        Action {
+            gas_limit: self.gas_limit,
+            dest_address: self.dest_address,
+            payments,
+            endpoint_name: self.endpoint_name,
+            arguments: self.arguments,
+        }
+    }
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct LegacyTransferProposal<M: ManagedTypeApi> {
+    pub actions: ManagedVec<M, LegacyAction<M>>,
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub enum LegacyProposalType<M: ManagedTypeApi> {
+    Nothing,
+
+    NewTransfer(LegacyTransferProposal<M>),
+}
+
+impl<M: ManagedTypeApi> LegacyProposalType<M> {
+    // legacy transfers were fire-and-forget calls, the closest mode is per action
+    pub fn into_proposal_type(self) -> ProposalType<M> {
+        match self {
+            LegacyProposalType::Nothing => ProposalType::Nothing,
+            LegacyProposalType::NewTransfer(transfer_proposal) => {
+                let mut actions = ManagedVec::new();
+                for action in transfer_proposal.actions.iter() {
+                    actions.push(action.clone().into_action());
+                }
+
+                ProposalType::NewTransfer(TransferProposal {
+                    actions,
+                    execution_mode: ExecutionMode::PerAction,
+                    category: ManagedBuffer::new(),
+                })
+            },
+        }
+    }
+}
+
+// proposal layout of the first release, before voting parameters were captured at creation
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct LegacyProposal<M: ManagedTypeApi> {
+    pub id: u64,
+    pub proposal_data: LegacyProposalType<M>,
+    pub proposal_type: ProposalTypeEnum,
+    pub creation_timestamp: u64,
+    pub proposer: ManagedAddress<M>,
+    pub title: ManagedBuffer<M>,
+    pub description: ManagedBuffer<M>,
+    pub status: ProposalStatus,
+    pub was_executed: bool,
+    pub num_upvotes: BigUint<M>,
+    pub num_downvotes: BigUint<M>,
+}
+
+#[multiversx_sc::module]
+pub trait MigrationModule:
+config::ConfigModule
++board_config::BoardConfigModule
++delegation_config::DelegationConfigModule
++staking_config::StakingConfigModule
++treasury_config::TreasuryConfigModule
++school_config::SchoolConfigModule
+{
+    #[view(getStorageVersion)]
+    #[storage_mapper("storage_version")]
+    fn storage_version(&self) -> SingleValueMapper<u8>;
+
+    #[storage_mapper("proposals")]
+    fn legacy_proposals(&self, id: u64) -> SingleValueMapper<LegacyProposal<Self::Api>>;
+
+    fn migrate_storage(&self) {
+        let version = self.storage_version().get();
+        if version < 1 {
+            self.migrate_proposals();
+        }
+        if version < 2 {
+            self.migrate_escrow();
+        }
+        if version < 3 {
+            self.migrate_student_relations();
+        }
+
+        self.storage_version().set(STORAGE_VERSION);
+    }
+
+    // existing proposals get the parameters in effect at upgrade time
+    fn migrate_proposals(&self) {
+        let voting_period = self.voting_period().get();
+        let quorum = self.quorum().get();
+        let participation_quorum = self.participation_quorum().get();
+        let approval_threshold = self.approval_threshold().get();
+        let voting_tokens = self.get_voting_tokens_snapshot();
+        let execution_delay = self.execution_delay().get();
+        let grace_period = self.grace_period().get();
+        for id in 0..self.last_proposal_id().get() {
+            if self.legacy_proposals(id).is_empty() {
+                continue;
+            }
+
+            let legacy = self.legacy_proposals(id).get();
+            let proposal = Proposal {
+                id: legacy.id,
+                proposal_data: legacy.proposal_data.into_proposal_type(),
+                proposal_type: legacy.proposal_type,
+                creation_timestamp: legacy.creation_timestamp,
+                proposer: legacy.proposer,
+                title: legacy.title,
+                description: legacy.description,
+                status: legacy.status,
+                was_executed: legacy.was_executed,
+                was_vetoed: false,
+                was_cancelled: false,
+                failed_action: None,
+                num_upvotes: legacy.num_upvotes,
+                num_downvotes: legacy.num_downvotes,
+                num_abstains: BigUint::zero(),
+                option_tallies: ManagedVec::new(),
+                raw_upvotes: BigUint::zero(),
+                raw_downvotes: BigUint::zero(),
+                raw_abstains: BigUint::zero(),
+                voting_start: legacy.creation_timestamp,
+                voting_end: legacy.creation_timestamp + voting_period,
+                quorum: quorum.clone(),
+                participation_quorum: participation_quorum.clone(),
+                approval_threshold,
+                veto_threshold: BigUint::zero(),
+                quorum_mode: QuorumMode::Absolute,
+                voting_tokens: voting_tokens.clone(),
+                execution_delay,
+                grace_period,
+                voting_mode: VotingMode::TokenWeighted,
+            };
+            self.proposals(id).set(&proposal);
+        }
+    }
+
+    // tokens held for voters, proposers, delegators and stakers before escrow was tracked
+    fn migrate_escrow(&self) {
+        for id in 0..self.last_proposal_id().get() {
+            for voter in self.proposal_voters(id).iter() {
+                self.escrow_payments(&self.voters_amounts(&voter, id).get());
+            }
+            if !self.proposal_deposits(id).is_empty() {
+                self.escrow_payment(&self.proposal_deposits(id).get().payment);
+            }
+        }
+        for delegate in self.delegates().iter() {
+            for delegator in self.delegators(&delegate).iter() {
+                self.escrow_payments(&self.delegations(&delegator).get().payments);
+            }
+        }
+        for staker in self.stakers().iter() {
+            self.escrow_payments(&self.stakes(&staker).get().payments);
+        }
+    }
+
+    // students enrolled before relations were recorded, employees are set by the board with `setEmployeeRelation`
+    fn migrate_student_relations(&self) {
+        for student_id in 0..self.last_student_id().get() {
+            if self.students(student_id).is_empty() {
+                continue;
+            }
+
+            let identity_id = self.students(student_id).get();
+            if self.identity_relations(identity_id).is_empty() {
+                self.identity_relations(identity_id).set(ManagedBuffer::from(STUDENT_RELATION));
+            }
+        }
+    }
+}
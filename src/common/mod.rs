@@ -3,3 +3,7 @@ pub mod consts;
 pub mod errors;
 pub mod school_config;
 pub mod board_config;
+pub mod migration;
+pub mod delegation_config;
+pub mod staking_config;
+pub mod treasury_config;
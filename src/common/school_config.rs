@@ -260,6 +260,28 @@ super::config::ConfigModule
             .execute_on_dest_context()
     }
 
+    // relation requested when the identity was registered with the school
+    #[view(getIdentityRelation)]
+    #[storage_mapper("identity_relations")]
+    fn identity_relations(&self, identity_id: u64) -> SingleValueMapper<ManagedBuffer>;
+
+    // the identity must still be linked to the school with one of the relations
+    fn is_school_member(&self, identity_id: u64, relations: &ManagedVec<ManagedBuffer>) -> bool {
+        if self.identity_relations(identity_id).is_empty() {
+            return false;
+        }
+
+        let relation = self.identity_relations(identity_id).get();
+        if !relations.contains(&relation) {
+            return false;
+        }
+
+        self.digital_identity_contract_proxy()
+            .contract(self.digital_identity_sc().get())
+            .is_parent_of(self.identity_id().get(), identity_id)
+            .execute_on_dest_context()
+    }
+
     #[view(getEmployeeIdByIdentityId)]
     fn get_employee_id_by_identity_id(&self, identity_id: u64) -> Option<u64> {
         for employee_id in 0..self.last_employee_id().get() {
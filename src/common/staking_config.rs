@@ -0,0 +1,55 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::common::consts::MAX_PERCENTAGE;
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug)]
+pub struct StakePosition<M: ManagedTypeApi> {
+    pub payments: ManagedVec<M, EsdtTokenPayment<M>>,
+    pub unlock_timestamp: u64,
+    // weight multiplier of the lock period that set the unlock time, in basis points
+    pub multiplier: u64,
+}
+
+impl<M: ManagedTypeApi> StakePosition<M> {
+    // tokens added to the position take its lock, so the new lock may not end sooner
+    pub fn can_relock(&self, unlock_timestamp: u64) -> bool {
+        unlock_timestamp >= self.unlock_timestamp
+    }
+
+    // the lock counts like the longest allowed lock that fits in the time left, never above its own multiplier
+    pub fn get_multiplier_at(&self, timestamp: u64, lock_multipliers: impl Iterator<Item = (u64, u64)>) -> u64 {
+        let remaining_lock = self.unlock_timestamp.saturating_sub(timestamp);
+        let mut multiplier = MAX_PERCENTAGE;
+        let mut bracket = 0;
+        for (lock_duration, lock_multiplier) in lock_multipliers {
+            if lock_duration <= remaining_lock && lock_duration >= bracket {
+                bracket = lock_duration;
+                multiplier = lock_multiplier;
+            }
+        }
+
+        core::cmp::min(multiplier, self.multiplier)
+    }
+}
+
+#[multiversx_sc::module]
+pub trait StakingConfigModule {
+    // lock duration => weight multiplier in basis points
+    #[view(getLockMultipliers)]
+    #[storage_mapper("lock_multipliers")]
+    fn lock_multipliers(&self) -> MapMapper<u64, u64>;
+
+    #[view(getStake)]
+    #[storage_mapper("stakes")]
+    fn stakes(&self, staker: &ManagedAddress) -> SingleValueMapper<StakePosition<Self::Api>>;
+
+    #[view(getStakers)]
+    #[storage_mapper("stakers")]
+    fn stakers(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getTotalStaked)]
+    #[storage_mapper("total_staked")]
+    fn total_staked(&self) -> MapMapper<TokenIdentifier, BigUint>;
+}
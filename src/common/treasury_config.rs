@@ -0,0 +1,384 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::common::{config::merge_payment, errors::*};
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Copy, Debug)]
+pub enum LedgerDirection {
+    Inflow,
+    Outflow,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Debug)]
+pub struct LedgerEntry<M: ManagedTypeApi> {
+    pub direction: LedgerDirection,
+    pub timestamp: u64,
+    // start of the budget period the entry is totalled in
+    pub period_start: u64,
+    // sender of an inflow, receiver of an outflow
+    pub address: ManagedAddress<M>,
+    pub payment: EgldOrEsdtTokenPayment<M>,
+    pub memo: ManagedBuffer<M>,
+    // set for outflows of proposal executions and for kept proposal deposits
+    pub proposal_id: Option<u64>,
+    pub action_index: Option<usize>,
+}
+
+#[multiversx_sc::module]
+pub trait TreasuryConfigModule {
+    // tokens held for voters, delegators, stakers and proposers, the treasury cannot spend them
+    #[view(getEscrowedAmount)]
+    #[storage_mapper("escrowed_amount")]
+    fn escrowed_amount(&self, token: &TokenIdentifier, nonce: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("escrowed_tokens")]
+    fn escrowed_tokens(&self) -> UnorderedSetMapper<(TokenIdentifier, u64)>;
+
+    fn escrow_payment(&self, payment: &EsdtTokenPayment) {
+        self.escrowed_amount(&payment.token_identifier, payment.token_nonce)
+            .update(|amount| *amount += &payment.amount);
+        self.escrowed_tokens().insert((payment.token_identifier.clone(), payment.token_nonce));
+    }
+
+    fn escrow_payments(&self, payments: &ManagedVec<EsdtTokenPayment>) {
+        for payment in payments.iter() {
+            self.escrow_payment(&payment);
+        }
+    }
+
+    fn release_payment(&self, payment: &EsdtTokenPayment) {
+        let escrowed = self.escrowed_amount(&payment.token_identifier, payment.token_nonce).get();
+        if escrowed > payment.amount {
+            self.escrowed_amount(&payment.token_identifier, payment.token_nonce).set(escrowed - &payment.amount);
+        } else {
+            self.escrowed_amount(&payment.token_identifier, payment.token_nonce).clear();
+            self.escrowed_tokens().swap_remove(&(payment.token_identifier.clone(), payment.token_nonce));
+        }
+    }
+
+    fn release_payments(&self, payments: &ManagedVec<EsdtTokenPayment>) {
+        for payment in payments.iter() {
+            self.release_payment(&payment);
+        }
+    }
+
+    // balance per token and nonce that execution can never spend
+    #[view(getMinReserve)]
+    #[storage_mapper("min_reserve")]
+    fn min_reserve(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64) -> SingleValueMapper<BigUint>;
+
+    fn get_free_balance(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64) -> BigUint {
+        let balance = self.blockchain().get_sc_balance(token, nonce);
+        let escrowed = match token.as_esdt_option() {
+            Some(esdt_token) => self.escrowed_amount(&esdt_token, nonce).get(),
+            None => BigUint::zero(),
+        };
+
+        if balance > escrowed {
+            balance - escrowed
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    // outgoing payments, summed per token and nonce, may only use the free balance above the reserve
+    fn require_free_balances(&self, payments: &ManagedVec<EgldOrEsdtTokenPayment>) {
+        let mut totals: ManagedVec<EgldOrEsdtTokenPayment> = ManagedVec::new();
+        for payment in payments.iter() {
+            merge_payment(&mut totals, payment.clone());
+        }
+
+        for total in totals.iter() {
+            let free_balance = self.get_free_balance(&total.token_identifier, total.token_nonce);
+            require!(
+                total.amount <= free_balance,
+                ERROR_NOT_ENOUGH_FREE_FUNDS,
+            );
+
+            // reserves are kept per nonce, like the balances they protect
+            let min_reserve = self.min_reserve(&total.token_identifier, total.token_nonce).get();
+            require!(&free_balance - &total.amount >= min_reserve, ERROR_MIN_RESERVE_BREACHED);
+        }
+    }
+
+    #[view(getEscrow)]
+    fn get_escrow(&self) -> ManagedVec<EsdtTokenPayment> {
+        let mut escrow = ManagedVec::new();
+        for (token, nonce) in self.escrowed_tokens().iter() {
+            let amount = self.escrowed_amount(&token, nonce).get();
+            escrow.push(EsdtTokenPayment::new(token, nonce, amount));
+        }
+
+        escrow
+    }
+
+    // free treasury balance and escrowed amount of a token
+    #[view(getTreasuryBalance)]
+    fn get_treasury_balance(&self, token: EgldOrEsdtTokenIdentifier, nonce: u64) -> MultiValue2<BigUint, BigUint> {
+        let escrowed = match token.as_esdt_option() {
+            Some(esdt_token) => self.escrowed_amount(&esdt_token, nonce).get(),
+            None => BigUint::zero(),
+        };
+
+        (self.get_free_balance(&token, nonce), escrowed).into()
+    }
+
+    // treasury movements, oldest first
+    #[storage_mapper("ledger")]
+    fn ledger(&self) -> VecMapper<LedgerEntry<Self::Api>>;
+
+    // running totals per token and budget period start, so reconciling never walks the ledger
+    #[storage_mapper("ledger_inflows")]
+    fn ledger_inflows(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64, period_start: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("ledger_outflows")]
+    fn ledger_outflows(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64, period_start: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("ledger_lifetime_inflows")]
+    fn ledger_lifetime_inflows(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("ledger_lifetime_outflows")]
+    fn ledger_lifetime_outflows(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64) -> SingleValueMapper<BigUint>;
+
+    fn record_ledger_entry(
+        &self,
+        direction: LedgerDirection,
+        address: &ManagedAddress,
+        payment: EgldOrEsdtTokenPayment,
+        memo: ManagedBuffer,
+        proposal_id: Option<u64>,
+        action_index: Option<usize>,
+    ) {
+        let period_start = self.get_current_budget_period();
+        let (period_mapper, lifetime_mapper) = match direction {
+            LedgerDirection::Inflow => (
+                self.ledger_inflows(&payment.token_identifier, payment.token_nonce, period_start),
+                self.ledger_lifetime_inflows(&payment.token_identifier, payment.token_nonce),
+            ),
+            LedgerDirection::Outflow => (
+                self.ledger_outflows(&payment.token_identifier, payment.token_nonce, period_start),
+                self.ledger_lifetime_outflows(&payment.token_identifier, payment.token_nonce),
+            ),
+        };
+        period_mapper.update(|total| *total += &payment.amount);
+        lifetime_mapper.update(|total| *total += &payment.amount);
+
+        self.ledger().push(&LedgerEntry {
+            direction,
+            timestamp: self.blockchain().get_block_timestamp(),
+            period_start,
+            address: address.clone(),
+            payment,
+            memo,
+            proposal_id,
+            action_index,
+        });
+    }
+
+    fn record_outflows(
+        &self,
+        proposal_id: u64,
+        action_index: usize,
+        dest_address: &ManagedAddress,
+        payments: &ManagedVec<EgldOrEsdtTokenPayment>,
+    ) {
+        for payment in payments.iter() {
+            self.record_ledger_entry(
+                LedgerDirection::Outflow,
+                dest_address,
+                payment.clone(),
+                ManagedBuffer::new(),
+                Some(proposal_id),
+                Some(action_index),
+            );
+        }
+    }
+
+    #[view(getLedgerLength)]
+    fn get_ledger_length(&self) -> usize {
+        self.ledger().len()
+    }
+
+    // view paginated ledger entries, indexes start at 0
+    #[view(getLedger)]
+    fn get_ledger(&self, idx_from: usize, idx_to: usize) -> MultiValueEncoded<LedgerEntry<Self::Api>> {
+        let mut entries = MultiValueEncoded::new();
+        let len = self.ledger().len();
+        for idx in idx_from..=idx_to {
+            if idx >= len {
+                break;
+            }
+
+            entries.push(self.ledger().get(idx + 1));
+        }
+
+        entries
+    }
+
+    // inflow and outflow totals of a token in the budget period starting at period_start
+    #[view(getLedgerTotals)]
+    fn get_ledger_totals(
+        &self,
+        token: EgldOrEsdtTokenIdentifier,
+        nonce: u64,
+        period_start: u64,
+    ) -> MultiValue2<BigUint, BigUint> {
+        let inflows = self.ledger_inflows(&token, nonce, period_start).get();
+        let outflows = self.ledger_outflows(&token, nonce, period_start).get();
+
+        (inflows, outflows).into()
+    }
+
+    // inflow and outflow totals of a token since the ledger started
+    #[view(getLedgerLifetimeTotals)]
+    fn get_ledger_lifetime_totals(&self, token: EgldOrEsdtTokenIdentifier, nonce: u64) -> MultiValue2<BigUint, BigUint> {
+        (self.ledger_lifetime_inflows(&token, nonce).get(), self.ledger_lifetime_outflows(&token, nonce).get()).into()
+    }
+
+    // budgets, spending per category is capped per token for each fiscal period
+    // start of the period running when the schedule last changed, later periods follow from it
+    #[storage_mapper("budget_period_start")]
+    fn budget_period_start(&self) -> SingleValueMapper<u64>;
+
+    // zero means a single period that never ends
+    #[storage_mapper("budget_period_length")]
+    fn budget_period_length(&self) -> SingleValueMapper<u64>;
+
+    // length of the periods after the current one ends, empty when unchanged
+    #[view(getPendingBudgetPeriodLength)]
+    #[storage_mapper("pending_budget_period_length")]
+    fn pending_budget_period_length(&self) -> SingleValueMapper<u64>;
+
+    #[view(getBudgetCategories)]
+    #[storage_mapper("budget_categories")]
+    fn budget_categories(&self) -> UnorderedSetMapper<ManagedBuffer>;
+
+    #[view(getBudgetCaps)]
+    #[storage_mapper("budget_caps")]
+    fn budget_caps(&self, category: &ManagedBuffer) -> MapMapper<EgldOrEsdtTokenIdentifier, BigUint>;
+
+    // spending is keyed by the start timestamp of its period, which later schedule changes never move
+    #[view(getBudgetSpent)]
+    #[storage_mapper("budget_spent")]
+    fn budget_spent(&self, category: &ManagedBuffer, period_start: u64) -> MapMapper<EgldOrEsdtTokenIdentifier, BigUint>;
+
+    // start and length of the running period
+    fn get_budget_period(&self) -> (u64, u64) {
+        let period_start = self.budget_period_start().get();
+        let period_length = self.budget_period_length().get();
+        let now = self.blockchain().get_block_timestamp();
+        if period_length == 0 || now < period_start + period_length {
+            return (period_start, period_length);
+        }
+
+        // a pending length only applies from the end of the period it was set in
+        let next_start = period_start + period_length;
+        let next_length = if self.pending_budget_period_length().is_empty() {
+            period_length
+        } else {
+            self.pending_budget_period_length().get()
+        };
+        if next_length == 0 {
+            return (next_start, next_length);
+        }
+
+        (next_start + (now - next_start) / next_length * next_length, next_length)
+    }
+
+    #[view(getCurrentBudgetPeriod)]
+    fn get_current_budget_period(&self) -> u64 {
+        self.get_budget_period().0
+    }
+
+    #[view(getBudgetPeriodLength)]
+    fn get_budget_period_length(&self) -> u64 {
+        self.get_budget_period().1
+    }
+
+    // a running period keeps its length, the new one applies from its end
+    fn change_budget_period_length(&self, period_length: u64) {
+        let (period_start, current_length) = self.get_budget_period();
+        if current_length == 0 {
+            // a period that never ends is closed now
+            self.budget_period_start().set(self.blockchain().get_block_timestamp());
+            self.budget_period_length().set(period_length);
+            self.pending_budget_period_length().clear();
+            return;
+        }
+
+        self.budget_period_start().set(period_start);
+        self.budget_period_length().set(current_length);
+        self.pending_budget_period_length().set(period_length);
+    }
+
+    #[view(getRemainingBudget)]
+    fn get_remaining_budget(&self, category: ManagedBuffer, token: EgldOrEsdtTokenIdentifier) -> BigUint {
+        let cap = self.budget_caps(&category).get(&token).unwrap_or_default();
+        let spent = self.budget_spent(&category, self.get_current_budget_period()).get(&token).unwrap_or_default();
+        if cap > spent {
+            cap - spent
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    // untagged transfers are only allowed while no budget is configured
+    fn require_within_budget(&self, category: &ManagedBuffer, totals: &ManagedVec<EgldOrEsdtTokenPayment>) {
+        if category.is_empty() {
+            require!(self.budget_categories().is_empty(), ERROR_MISSING_BUDGET_CATEGORY);
+            return;
+        }
+
+        require!(self.budget_categories().contains(category), ERROR_UNKNOWN_BUDGET_CATEGORY);
+        for total in totals.iter() {
+            require!(
+                total.amount <= self.get_remaining_budget(category.clone(), total.token_identifier.clone()),
+                ERROR_BUDGET_EXCEEDED,
+            );
+        }
+    }
+
+    fn charge_budget(&self, category: &ManagedBuffer, period_start: u64, totals: &ManagedVec<EgldOrEsdtTokenPayment>) {
+        if category.is_empty() {
+            return;
+        }
+
+        let mut spent_mapper = self.budget_spent(category, period_start);
+        for total in totals.iter() {
+            let spent = spent_mapper.get(&total.token_identifier).unwrap_or_default();
+            spent_mapper.insert(total.token_identifier.clone(), spent + &total.amount);
+        }
+    }
+
+    // gives back the budget of a failed action to the period it was charged in
+    fn refund_budget(&self, category: &ManagedBuffer, period_start: u64, payments: &ManagedVec<EgldOrEsdtTokenPayment>) {
+        if category.is_empty() {
+            return;
+        }
+
+        let mut spent_mapper = self.budget_spent(category, period_start);
+        for payment in payments.iter() {
+            let spent = spent_mapper.get(&payment.token_identifier).unwrap_or_default();
+            if spent > payment.amount {
+                spent_mapper.insert(payment.token_identifier.clone(), spent - &payment.amount);
+            } else {
+                spent_mapper.remove(&payment.token_identifier);
+            }
+        }
+    }
+
+    // totals per token, whatever the nonce
+    fn sum_per_token(&self, payments: &ManagedVec<EgldOrEsdtTokenPayment>) -> ManagedVec<EgldOrEsdtTokenPayment> {
+        let mut totals: ManagedVec<EgldOrEsdtTokenPayment> = ManagedVec::new();
+        for payment in payments.iter() {
+            merge_payment(
+                &mut totals,
+                EgldOrEsdtTokenPayment::new(payment.token_identifier.clone(), 0, payment.amount.clone()),
+            );
+        }
+
+        totals
+    }
+}
@@ -0,0 +1,152 @@
+multiversx_sc::imports!();
+
+use crate::common::errors::*;
+use crate::common::config::{self, merge_payment, Proposal, State, Vote, VoteType};
+use crate::common::delegation_config::{self, *};
+use crate::common::board_config;
+use crate::common::treasury_config;
+
+#[multiversx_sc::module]
+pub trait DelegationModule:
+delegation_config::DelegationConfigModule
++treasury_config::TreasuryConfigModule
++board_config::BoardConfigModule
++config::ConfigModule
+{
+    // deposits voting tokens and delegates their voting power, calling it again without payment moves the delegation
+    #[payable("*")]
+    #[endpoint(delegate)]
+    fn delegate(&self, to: ManagedAddress) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let caller = self.blockchain().get_caller();
+        require!(caller != to, ERROR_CANNOT_DELEGATE_TO_SELF);
+
+        let payments = self.call_value().all_esdt_transfers().clone_value();
+        for payment in payments.iter() {
+            require!(self.voting_tokens().contains_key(&payment.token_identifier), ERROR_INVALID_PAYMENT);
+            require!(payment.amount > 0, ERROR_ZERO_PAYMENT);
+        }
+        if !self.delegators(&to).contains(&caller) {
+            require!(self.delegators(&to).len() < self.delegation_limit(&to).get(), ERROR_DELEGATION_LIMIT_REACHED);
+        }
+
+        let mut delegation = if self.delegations(&caller).is_empty() {
+            require!(!payments.is_empty(), ERROR_ZERO_PAYMENT);
+            Delegation {
+                delegate: to.clone(),
+                payments: ManagedVec::new(),
+                locked_until: 0,
+            }
+        } else {
+            self.delegations(&caller).get()
+        };
+
+        if delegation.delegate != to {
+            require!(self.blockchain().get_block_timestamp() >= delegation.locked_until, ERROR_DELEGATION_LOCKED);
+
+            self.remove_delegator(&delegation.delegate, &caller);
+            delegation.delegate = to.clone();
+        }
+
+        for payment in payments.iter() {
+            merge_payment(&mut delegation.payments, payment.clone());
+            self.escrow_payment(&payment);
+        }
+
+        self.delegations(&caller).set(&delegation);
+        self.delegators(&to).insert(caller);
+        self.delegates().insert(to);
+    }
+
+    // opts in to receiving delegations, a lower limit keeps the current delegators
+    #[endpoint(setDelegationLimit)]
+    fn set_delegation_limit(&self, max_delegators: usize) {
+        let caller = self.blockchain().get_caller();
+        self.delegation_limit(&caller).set(max_delegators);
+    }
+
+    // returns the delegated tokens once no running vote counts them anymore
+    #[endpoint(undelegate)]
+    fn undelegate(&self) {
+        let caller = self.blockchain().get_caller();
+        require!(!self.delegations(&caller).is_empty(), ERROR_NO_DELEGATION);
+
+        let delegation = self.delegations(&caller).get();
+        require!(self.blockchain().get_block_timestamp() >= delegation.locked_until, ERROR_DELEGATION_LOCKED);
+
+        self.delegations(&caller).clear();
+        self.remove_delegator(&delegation.delegate, &caller);
+        self.release_payments(&delegation.payments);
+        self.send().direct_multi(&caller, &delegation.payments);
+    }
+
+    // the voter's delegated tokens, which they take back by voting themselves
+    fn get_delegated_tokens(&self, voter: &ManagedAddress, proposal: &Proposal<Self::Api>) -> ManagedVec<EsdtTokenPayment> {
+        if self.delegations(voter).is_empty() {
+            return ManagedVec::new();
+        }
+
+        self.lock_delegation(voter, proposal.voting_end);
+        self.delegations(voter).get().payments
+    }
+
+    // votes for every delegator that did not vote on the proposal themselves
+    fn cast_delegated_votes(
+        &self,
+        delegate: &ManagedAddress,
+        proposal: &mut Proposal<Self::Api>,
+        vote_type: &VoteType,
+        option: Option<usize>,
+    ) {
+        for delegator in self.delegators(delegate).iter() {
+            if !self.votes(&delegator, proposal.id).is_empty() {
+                continue;
+            }
+
+            let payments = self.delegations(&delegator).get().payments;
+            let weight = proposal.get_vote_weight(&payments);
+            let raw_weight = proposal.get_raw_vote_weight(&payments);
+            proposal.update_tally(vote_type, option, &BigUint::zero(), &weight);
+            proposal.update_raw_tally(vote_type, &BigUint::zero(), &raw_weight);
+            self.votes(&delegator, proposal.id).set(Vote {
+                vote_type: vote_type.clone(),
+                weight,
+                raw_weight,
+                delegate: Some(delegate.clone()),
+                option,
+            });
+            self.lock_delegation(&delegator, proposal.voting_end);
+        }
+    }
+
+    // delegated votes follow the delegate when they change sides
+    fn move_delegated_votes(&self, delegate: &ManagedAddress, proposal: &mut Proposal<Self::Api>, vote_type: &VoteType) {
+        let zero = BigUint::zero();
+        for delegator in self.delegators(delegate).iter() {
+            if self.votes(&delegator, proposal.id).is_empty() {
+                continue;
+            }
+
+            let mut vote = self.votes(&delegator, proposal.id).get();
+            if vote.delegate.as_ref() != Some(delegate) {
+                continue;
+            }
+
+            proposal.update_tally(&vote.vote_type, vote.option, &vote.weight, &zero);
+            proposal.update_tally(vote_type, vote.option, &zero, &vote.weight);
+            proposal.update_raw_tally(&vote.vote_type, &vote.raw_weight, &zero);
+            proposal.update_raw_tally(vote_type, &zero, &vote.raw_weight);
+            vote.vote_type = vote_type.clone();
+            self.votes(&delegator, proposal.id).set(vote);
+        }
+    }
+
+    fn lock_delegation(&self, delegator: &ManagedAddress, until: u64) {
+        self.delegations(delegator).update(|delegation| {
+            if delegation.locked_until < until {
+                delegation.locked_until = until;
+            }
+        });
+    }
+}
@@ -6,8 +6,10 @@ pub mod common;
 pub mod school;
 pub mod proxies;
 pub mod multisig;
+pub mod delegation;
+pub mod staking;
 
-use common::{config::*, consts::*, errors::*};
+use common::{board_config::BoardAction, config::*, consts::*, errors::*, treasury_config::LedgerDirection};
 use tfn_dao::common::config::ProxyTrait as _;
 use crate::proxies::launchpad_proxy::{self};
 
@@ -18,6 +20,12 @@ common::config::ConfigModule
 +common::board_config::BoardConfigModule
 +school::SchoolModule
 +multisig::MultisigModule
++common::migration::MigrationModule
++common::delegation_config::DelegationConfigModule
++delegation::DelegationModule
++common::staking_config::StakingConfigModule
++staking::StakingModule
++common::treasury_config::TreasuryConfigModule
 {
     #[init]
     fn init(
@@ -26,6 +34,7 @@ common::config::ConfigModule
         token: &TokenIdentifier,
     ) {
         self.governance_token().set(token);
+        self.storage_version().set(STORAGE_VERSION);
         let caller = self.blockchain().get_caller();
         if !self.blockchain().is_smart_contract(&caller) {
             return
@@ -55,108 +64,742 @@ common::config::ConfigModule
 
     #[upgrade]
     fn upgrade(&self) {
+        self.migrate_storage();
     }
 
+    // every payment is recorded in the treasury ledger with the optional memo
     #[payable("*")]
     #[endpoint(addFunds)]
-    fn add_funds(&self) {}
+    fn add_funds(&self, opt_memo: OptionalValue<ManagedBuffer>) {
+        let caller = self.blockchain().get_caller();
+        let memo = opt_memo.into_option().unwrap_or_default();
+        let payments = match self.call_value().any_payment() {
+            EgldOrMultiEsdtPayment::Egld(amount) => {
+                require!(amount > 0, ERROR_ZERO_PAYMENT);
+
+                ManagedVec::from_single_item(EgldOrEsdtTokenPayment::new(EgldOrEsdtTokenIdentifier::egld(), 0, amount))
+            },
+            EgldOrMultiEsdtPayment::MultiEsdt(esdt_payments) => {
+                require!(!esdt_payments.is_empty(), ERROR_ZERO_PAYMENT);
 
+                let mut payments = ManagedVec::new();
+                for payment in esdt_payments.iter() {
+                    payments.push(EgldOrEsdtTokenPayment::from(payment.clone()));
+                }
+                payments
+            },
+        };
+
+        for payment in payments.iter() {
+            self.record_ledger_entry(LedgerDirection::Inflow, &caller, payment.clone(), memo.clone(), None, None);
+        }
+    }
+
+    #[payable("*")]
     #[endpoint(proposeNewTransfer)]
     fn propose_new_transfer(
         &self,
         title: ManagedBuffer,
         description: ManagedBuffer,
         transfer_proposal: TransferProposal<Self::Api>,
+        opt_voting_period: OptionalValue<u64>,
+    ) -> u64 {
+        self.create_proposal(
+            title,
+            description,
+            ProposalType::NewTransfer(transfer_proposal),
+            VotingMode::TokenWeighted,
+            opt_voting_period.into_option(),
+        )
+    }
+
+    #[payable("*")]
+    #[endpoint(proposeBoardAction)]
+    fn propose_board_action(
+        &self,
+        title: ManagedBuffer,
+        description: ManagedBuffer,
+        action: BoardAction<Self::Api>,
+        opt_voting_period: OptionalValue<u64>,
+    ) -> u64 {
+        self.create_proposal(
+            title,
+            description,
+            ProposalType::BoardAction(action),
+            VotingMode::TokenWeighted,
+            opt_voting_period.into_option(),
+        )
+    }
+
+    // routine spending that passes unless vetoed by downvotes
+    #[payable("*")]
+    #[endpoint(proposeOptimisticTransfer)]
+    fn propose_optimistic_transfer(
+        &self,
+        title: ManagedBuffer,
+        description: ManagedBuffer,
+        transfer_proposal: TransferProposal<Self::Api>,
+        opt_voting_period: OptionalValue<u64>,
+    ) -> u64 {
+        self.create_proposal(
+            title,
+            description,
+            ProposalType::OptimisticTransfer(transfer_proposal),
+            VotingMode::TokenWeighted,
+            opt_voting_period.into_option(),
+        )
+    }
+
+    // non-binding proposal whose outcome is only recorded
+    #[payable("*")]
+    #[endpoint(proposeSignal)]
+    fn propose_signal(
+        &self,
+        title: ManagedBuffer,
+        description: ManagedBuffer,
+        opt_voting_period: OptionalValue<u64>,
+    ) -> u64 {
+        self.create_proposal(
+            title,
+            description,
+            ProposalType::Nothing,
+            VotingMode::TokenWeighted,
+            opt_voting_period.into_option(),
+        )
+    }
+
+    #[payable("*")]
+    #[endpoint(proposePoll)]
+    fn propose_poll(
+        &self,
+        title: ManagedBuffer,
+        description: ManagedBuffer,
+        options: ManagedVec<ManagedBuffer>,
+        opt_voting_period: OptionalValue<u64>,
+    ) -> u64 {
+        self.create_proposal(
+            title,
+            description,
+            ProposalType::Poll(options),
+            VotingMode::TokenWeighted,
+            opt_voting_period.into_option(),
+        )
+    }
+
+    // one identity one vote among the school members linked with the given relations
+    #[payable("*")]
+    #[endpoint(proposeReferendum)]
+    fn propose_referendum(
+        &self,
+        title: ManagedBuffer,
+        description: ManagedBuffer,
+        proposal_data: ProposalType<Self::Api>,
+        relations: ManagedVec<ManagedBuffer>,
+        opt_voting_period: OptionalValue<u64>,
+    ) -> u64 {
+        require!(self.referendum_quorum().get() > 0, ERROR_REFERENDUMS_DISABLED);
+        require!(
+            matches!(proposal_data, ProposalType::Nothing | ProposalType::Poll(_)) || self.binding_referendums().get(),
+            ERROR_BINDING_REFERENDUMS_DISABLED,
+        );
+
+        let mut known_relations: ManagedVec<ManagedBuffer> = ManagedVec::new();
+        known_relations.push(ManagedBuffer::from(STUDENT_RELATION));
+        known_relations.push(ManagedBuffer::from(TEACHER_RELATION));
+        known_relations.push(ManagedBuffer::from(EMPLOYEE_RELATION));
+
+        require!(!relations.is_empty(), ERROR_INVALID_RELATION);
+        for relation in relations.iter() {
+            require!(known_relations.contains(&relation), ERROR_INVALID_RELATION);
+        }
+
+        self.create_proposal(
+            title,
+            description,
+            proposal_data,
+            VotingMode::Referendum(relations),
+            opt_voting_period.into_option(),
+        )
+    }
+
+    fn create_proposal(
+        &self,
+        title: ManagedBuffer,
+        description: ManagedBuffer,
+        proposal_data: ProposalType<Self::Api>,
+        voting_mode: VotingMode<Self::Api>,
+        opt_voting_period: Option<u64>,
     ) -> u64 {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
 
         let caller = self.blockchain().get_caller();
-        require!(self.board_members().contains(&caller), ERROR_ONLY_BOARD_MEMBERS);
-
+        let opt_deposit = self.take_proposal_deposit();
+        self.require_can_propose(&caller, &opt_deposit, &proposal_data);
+        self.require_open_proposals_limit(&caller);
+
+        self.validate_proposal_data(&proposal_data);
+        let voting_period = match opt_voting_period {
+            Some(voting_period) => {
+                require!(
+                    voting_period > 0 &&
+                        voting_period >= self.min_voting_period().get() &&
+                        voting_period <= self.max_voting_period().get(),
+                    ERROR_INVALID_VOTING_PERIOD,
+                );
+                voting_period
+            },
+            None => self.voting_period().get(),
+        };
+        let current_timestamp = self.blockchain().get_block_timestamp();
+        let voting_start = current_timestamp + self.voting_delay().get();
+        let voting_tokens = self.get_voting_tokens_snapshot();
+        // referendums count identities, not token weight
+        let (quorum, participation_quorum) = match &voting_mode {
+            VotingMode::TokenWeighted => (
+                self.resolve_quorum(self.quorum().get(), &voting_tokens),
+                self.resolve_quorum(self.participation_quorum().get(), &voting_tokens),
+            ),
+            VotingMode::Referendum(_) => (BigUint::zero(), BigUint::from(self.referendum_quorum().get())),
+        };
+        // no supply set or nothing staked yet would let any majority pass
+        require!(matches!(voting_mode, VotingMode::Referendum(_)) || quorum > 0, ERROR_ZERO_RESOLVED_QUORUM);
+        let urgency_multiplier = self.get_urgency_quorum_multiplier(voting_period);
+        let veto_threshold = match (&proposal_data, &voting_mode) {
+            (ProposalType::OptimisticTransfer(_), VotingMode::TokenWeighted) => {
+                let veto_threshold = self.resolve_quorum(self.optimistic_veto_threshold().get(), &voting_tokens);
+                require!(veto_threshold > 0, ERROR_OPTIMISTIC_DISABLED);
+                veto_threshold
+            },
+            (ProposalType::OptimisticTransfer(_), VotingMode::Referendum(_)) => sc_panic!(ERROR_OPTIMISTIC_REFERENDUM),
+            _ => BigUint::zero(),
+        };
+        let option_tallies = proposal_data.new_option_tallies();
+        let quorum_mode = match &voting_mode {
+            VotingMode::TokenWeighted => self.quorum_mode().get(),
+            VotingMode::Referendum(_) => QuorumMode::Absolute,
+        };
         let proposal = Proposal {
             id: self.last_proposal_id().get(),
-            proposal_data: ProposalType::NewTransfer(transfer_proposal),
-            proposal_type: ProposalTypeEnum::NewTransfer,
-            creation_timestamp: self.blockchain().get_block_timestamp(),
+            proposal_type: proposal_data.get_type(),
+            proposal_data,
+            creation_timestamp: current_timestamp,
             proposer: caller,
             title,
             description,
             status: ProposalStatus::Pending,
             was_executed: false,
+            was_vetoed: false,
+            was_cancelled: false,
+            failed_action: None,
             num_upvotes: BigUint::zero(),
             num_downvotes: BigUint::zero(),
+            num_abstains: BigUint::zero(),
+            option_tallies,
+            raw_upvotes: BigUint::zero(),
+            raw_downvotes: BigUint::zero(),
+            raw_abstains: BigUint::zero(),
+            voting_start,
+            voting_end: voting_start + voting_period,
+            quorum: quorum * urgency_multiplier / MAX_PERCENTAGE,
+            participation_quorum: participation_quorum * urgency_multiplier / MAX_PERCENTAGE,
+            veto_threshold,
+            quorum_mode,
+            approval_threshold: self.approval_threshold().get(),
+            voting_tokens,
+            execution_delay: self.execution_delay().get(),
+            grace_period: self.grace_period().get(),
+            voting_mode,
         };
         self.proposals(proposal.id).set(&proposal);
         self.last_proposal_id().set(proposal.id + 1);
+        self.proposer_proposals(&proposal.proposer).insert(proposal.id);
+        if let Some(payment) = opt_deposit {
+            self.escrow_payment(&payment);
+            self.proposal_deposits(proposal.id).set(ProposalDeposit {
+                payment,
+                refund_turnout: self.deposit_refund_turnout().get(),
+            });
+        }
 
         proposal.id
     }
 
+    #[endpoint(editProposal)]
+    fn edit_proposal(
+        &self,
+        proposal_id: u64,
+        title: ManagedBuffer,
+        description: ManagedBuffer,
+        proposal_data: ProposalType<Self::Api>,
+    ) {
+        let mut proposal = self.get_pending_proposal_of_caller(proposal_id);
+        require!(proposal_data.get_type() == proposal.proposal_type, ERROR_PROPOSAL_TYPE_MISMATCH);
+        // the new content must be something the proposer could have proposed in the first place
+        let opt_deposit = if self.proposal_deposits(proposal_id).is_empty() {
+            None
+        } else {
+            Some(self.proposal_deposits(proposal_id).get().payment)
+        };
+        self.require_can_propose(&proposal.proposer, &opt_deposit, &proposal_data);
+        self.validate_proposal_data(&proposal_data);
+
+        self.proposal_revisions(proposal_id).push(&ProposalRevision {
+            title: proposal.title,
+            description: proposal.description,
+            proposal_data: proposal.proposal_data,
+            timestamp: self.blockchain().get_block_timestamp(),
+        });
+        proposal.title = title;
+        proposal.description = description;
+        proposal.option_tallies = proposal_data.new_option_tallies();
+        proposal.proposal_data = proposal_data;
+        self.proposals(proposal_id).set(&proposal);
+    }
+
+    #[endpoint(withdrawProposal)]
+    fn withdraw_proposal(&self, proposal_id: u64) {
+        let mut proposal = self.get_pending_proposal_of_caller(proposal_id);
+        proposal.was_cancelled = true;
+        self.proposals(proposal_id).set(&proposal);
+        self.proposer_proposals(&proposal.proposer).swap_remove(&proposal_id);
+
+        if !self.proposal_deposits(proposal_id).is_empty() {
+            let deposit = self.proposal_deposits(proposal_id).take();
+            self.release_payment(&deposit.payment);
+            self.send().direct_esdt(
+                &proposal.proposer,
+                &deposit.payment.token_identifier,
+                deposit.payment.token_nonce,
+                &deposit.payment.amount,
+            );
+        }
+    }
+
+    fn get_pending_proposal_of_caller(&self, proposal_id: u64) -> Proposal<Self::Api> {
+        require!(!self.proposals(proposal_id).is_empty(), ERROR_PROPOSAL_NOT_FOUND);
+
+        let proposal = self.proposals(proposal_id).get();
+        require!(self.blockchain().get_caller() == proposal.proposer, ERROR_ONLY_PROPOSER);
+        require!(self.get_proposal_status(&proposal) == ProposalStatus::Pending, ERROR_PROPOSAL_NOT_PENDING);
+
+        proposal
+    }
+
+    fn take_proposal_deposit(&self) -> Option<EsdtTokenPayment> {
+        let payments = self.call_value().all_esdt_transfers().clone_value();
+        let deposit_amount = self.proposal_deposit_amount().get();
+        if deposit_amount == 0 {
+            require!(payments.is_empty(), ERROR_INVALID_PAYMENT);
+            return None;
+        }
+
+        require!(payments.len() == 1, ERROR_NOT_ENOUGH_FUNDS_TO_PROPOSE);
+        let payment = payments.get(0).clone();
+        require!(payment.token_identifier == self.governance_token().get(), ERROR_INVALID_PAYMENT);
+        require!(payment.amount >= deposit_amount, ERROR_NOT_ENOUGH_FUNDS_TO_PROPOSE);
+
+        Some(payment)
+    }
+
+    // board members can always propose, token holders above the threshold in TokenHolders mode
+    // or, whatever the mode, to change the board itself
+    fn require_can_propose(
+        &self,
+        proposer: &ManagedAddress,
+        opt_deposit: &Option<EsdtTokenPayment>,
+        proposal_data: &ProposalType<Self::Api>,
+    ) {
+        if self.board_members().contains(proposer) {
+            return;
+        }
+
+        let changes_board = matches!(proposal_data, ProposalType::BoardAction(action) if action.changes_board());
+        require!(
+            changes_board || self.proposer_mode().get() == ProposerMode::TokenHolders,
+            ERROR_ONLY_BOARD_MEMBERS,
+        );
+        // only locked tokens count, wallet balances could be moved around to pass the threshold again
+        let mut voting_power = self.get_staked_voting_power(proposer);
+        if let Some(deposit) = opt_deposit {
+            if let Some(weight) = self.get_voting_token_weight(&deposit.token_identifier, deposit.token_nonce) {
+                voting_power += &deposit.amount * &weight / ONE;
+            }
+        }
+        require!(voting_power >= self.proposer_threshold().get(), ERROR_PROPOSER_THRESHOLD_NOT_MET);
+        require!(!changes_board || voting_power > 0, ERROR_NO_VOTING_POWER);
+    }
+
+    fn require_open_proposals_limit(&self, proposer: &ManagedAddress) {
+        // forget the proposals whose voting ended
+        let mut closed_proposals: ManagedVec<u64> = ManagedVec::new();
+        for proposal_id in self.proposer_proposals(proposer).iter() {
+            let proposal = self.proposals(proposal_id).get();
+            if self.is_voting_closed(&self.get_proposal_status(&proposal)) {
+                closed_proposals.push(proposal_id);
+            }
+        }
+        for proposal_id in closed_proposals.iter() {
+            self.proposer_proposals(proposer).swap_remove(&proposal_id);
+        }
+
+        let max_open_proposals = self.max_open_proposals().get();
+        require!(
+            max_open_proposals == 0 || self.proposer_proposals(proposer).len() < max_open_proposals,
+            ERROR_TOO_MANY_OPEN_PROPOSALS,
+        );
+    }
+
+    // refunds the proposer's deposit or keeps it in the treasury, once voting ended
+    #[endpoint(settleDeposit)]
+    fn settle_deposit(&self, proposal_id: u64) {
+        require!(!self.proposals(proposal_id).is_empty(), ERROR_PROPOSAL_NOT_FOUND);
+        require!(!self.proposal_deposits(proposal_id).is_empty(), ERROR_NO_DEPOSIT);
+
+        let proposal = self.proposals(proposal_id).get();
+        let pstat = self.get_proposal_status(&proposal);
+        require!(self.is_voting_closed(&pstat), ERROR_VOTING_PERIOD_NOT_ENDED);
+
+        let deposit = self.proposal_deposits(proposal_id).take();
+        // a kept deposit joins the free treasury funds
+        self.release_payment(&deposit.payment);
+        let turnout = proposal.get_participation();
+        let refundable = pstat != ProposalStatus::Vetoed &&
+            (self.is_proposal_approved(&proposal) || turnout >= deposit.refund_turnout);
+        if refundable {
+            self.send().direct_esdt(
+                &proposal.proposer,
+                &deposit.payment.token_identifier,
+                deposit.payment.token_nonce,
+                &deposit.payment.amount,
+            );
+        } else {
+            self.record_ledger_entry(
+                LedgerDirection::Inflow,
+                &proposal.proposer,
+                EgldOrEsdtTokenPayment::from(deposit.payment),
+                ManagedBuffer::from(KEPT_DEPOSIT_MEMO),
+                Some(proposal_id),
+                None,
+            );
+        }
+    }
+
+    // turns a quorum given in basis points of the eligible tokens into raw vote weight
+    fn resolve_quorum(&self, quorum: BigUint, voting_tokens: &ManagedVec<VotingTokenWeight<Self::Api>>) -> BigUint {
+        let eligible_tokens = match self.quorum_mode().get() {
+            QuorumMode::Absolute => return quorum,
+            QuorumMode::SupplyPercentage => self.voting_token_supply(),
+            QuorumMode::StakedPercentage => self.total_staked(),
+        };
+
+        let mut eligible_share = ManagedVec::new();
+        for (token, amount) in eligible_tokens.iter() {
+            eligible_share.push(EsdtTokenPayment::new(token, 0, amount * &quorum / MAX_PERCENTAGE));
+        }
+
+        compute_raw_weight(voting_tokens, &eligible_share)
+    }
+
+    fn validate_proposal_data(&self, proposal_data: &ProposalType<Self::Api>) {
+        match proposal_data {
+            ProposalType::Nothing => {},
+            ProposalType::NewTransfer(transfer_proposal) => self.validate_transfer_proposal(transfer_proposal),
+            ProposalType::BoardAction(action) => {
+                require!(!action.is_reserve_action(), ERROR_BOARD_ONLY_ACTION);
+                self.require_valid_board_action(action);
+            },
+            ProposalType::Poll(options) => require!(options.len() >= 2, ERROR_NOT_ENOUGH_OPTIONS),
+            ProposalType::OptimisticTransfer(transfer_proposal) => {
+                self.validate_transfer_proposal(transfer_proposal);
+                self.validate_optimistic_spending(transfer_proposal);
+            },
+        };
+    }
+
+    fn validate_optimistic_spending(&self, transfer_proposal: &TransferProposal<Self::Api>) {
+        let totals = self.sum_per_token(&transfer_proposal.get_payments());
+        for total in totals.iter() {
+            let cap = self.optimistic_spending_caps().get(&total.token_identifier).unwrap_or_default();
+            require!(total.amount <= cap, ERROR_OPTIMISTIC_CAP_EXCEEDED);
+        }
+    }
+
+    fn validate_transfer_proposal(&self, transfer_proposal: &TransferProposal<Self::Api>) {
+        for action in transfer_proposal.actions.iter() {
+            for payment in action.payments.iter() {
+                require!(payment.amount > 0, ERROR_ZERO_PAYMENT);
+                require!(
+                    payment.token_identifier.is_esdt() || action.payments.len() == 1,
+                    ERROR_EGLD_WITH_OTHER_PAYMENTS,
+                );
+            }
+        }
+
+        if transfer_proposal.execution_mode == ExecutionMode::Atomic {
+            let own_shard = self.blockchain().get_shard_of_address(&self.blockchain().get_sc_address());
+            for action in transfer_proposal.actions.iter() {
+                require!(
+                    self.blockchain().get_shard_of_address(&action.dest_address) == own_shard,
+                    ERROR_ATOMIC_CROSS_SHARD,
+                );
+            }
+        }
+
+        self.require_within_budget(&transfer_proposal.category, &self.sum_per_token(&transfer_proposal.get_payments()));
+    }
+
     #[payable("*")]
     #[endpoint(upvote)]
     fn upvote(&self, proposal_id: u64) {
-        self.vote(proposal_id, VoteType::Upvote)
+        self.vote(proposal_id, VoteType::Upvote, None)
     }
 
     #[payable("*")]
     #[endpoint(downvote)]
     fn downvote(&self, proposal_id: u64) {
-        self.vote(proposal_id, VoteType::DownVote)
+        self.vote(proposal_id, VoteType::DownVote, None)
     }
 
-    fn vote(&self, proposal_id: u64, vote_type: VoteType) {
-        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
-        require!(!self.proposals(proposal_id).is_empty(), ERROR_PROPOSAL_NOT_FOUND);
+    #[payable("*")]
+    #[endpoint(abstain)]
+    fn abstain(&self, proposal_id: u64) {
+        self.vote(proposal_id, VoteType::Abstain, None)
+    }
 
-        let mut proposal = self.proposals(proposal_id).get();
-        let pstat = self.get_proposal_status(&proposal);
-        require!(pstat == ProposalStatus::Active, ERROR_PROPOSAL_NOT_ACTIVE);
+    // poll votes count as upvotes towards participation
+    #[payable("*")]
+    #[endpoint(voteOption)]
+    fn vote_option(&self, proposal_id: u64, option: usize) {
+        self.vote(proposal_id, VoteType::Upvote, Some(option))
+    }
 
-        let payment = self.call_value().single_esdt();
-        require!(self.voting_tokens().contains_key(&payment.token_identifier), ERROR_INVALID_PAYMENT);
-        require!(payment.amount > 0, ERROR_ZERO_PAYMENT);
+    fn vote(&self, proposal_id: u64, vote_type: VoteType, option: Option<usize>) {
+        let mut proposal = self.get_active_proposal(proposal_id);
+        match (&proposal.proposal_data, option) {
+            (ProposalType::Poll(options), Some(option)) => require!(option < options.len(), ERROR_INVALID_OPTION),
+            (ProposalType::Poll(_), None) | (_, Some(_)) => sc_panic!(ERROR_INVALID_OPTION),
+            _ => {},
+        };
+        if let VotingMode::Referendum(relations) = &proposal.voting_mode {
+            let relations = relations.clone();
+            return self.vote_in_referendum(proposal, &relations, vote_type, option);
+        }
+
+        let caller = self.blockchain().get_caller();
+        self.require_no_legacy_deposit(&caller, proposal.id);
+
+        let mut vote = if self.votes(&caller, proposal.id).is_empty() {
+            Vote {
+                vote_type: vote_type.clone(),
+                weight: BigUint::zero(),
+                raw_weight: BigUint::zero(),
+                delegate: None,
+                option,
+            }
+        } else {
+            self.votes(&caller, proposal.id).get()
+        };
+        if vote.delegate.is_some() {
+            // voting in person overrides the vote cast by the delegate
+            proposal.update_tally(&vote.vote_type, vote.option, &vote.weight, &BigUint::zero());
+            proposal.update_raw_tally(&vote.vote_type, &vote.raw_weight, &BigUint::zero());
+            vote = Vote {
+                vote_type: vote_type.clone(),
+                weight: BigUint::zero(),
+                raw_weight: BigUint::zero(),
+                delegate: None,
+                option,
+            };
+        }
+        require!(vote.vote_type == vote_type && vote.option == option, ERROR_VOTE_TYPE_MISMATCH);
 
-        let vote_weight = payment.amount.clone() * self.voting_tokens().get(&payment.token_identifier).unwrap() / ONE;
-        match vote_type {
-            VoteType::Upvote => proposal.num_upvotes += vote_weight.sqrt(),
-            VoteType::DownVote => proposal.num_downvotes += vote_weight.sqrt(),
+        let payments = self.call_value().all_esdt_transfers().clone_value();
+        for payment in payments.iter() {
+            self.add_voter_amount(&caller, &proposal, payment.clone());
         }
+
+        let voter_tokens = self.get_voter_tokens(&caller, &proposal);
+        let new_weight = proposal.get_vote_weight(&voter_tokens);
+        require!(new_weight > 0 || !self.delegators(&caller).is_empty(), ERROR_NO_VOTING_POWER);
+        let new_raw_weight = proposal.get_raw_vote_weight(&voter_tokens);
+
+        proposal.update_tally(&vote_type, option, &vote.weight, &new_weight);
+        proposal.update_raw_tally(&vote_type, &vote.raw_weight, &new_raw_weight);
+        self.cast_delegated_votes(&caller, &mut proposal, &vote_type, option);
         self.proposals(proposal_id).set(&proposal);
 
+        vote.weight = new_weight;
+        vote.raw_weight = new_raw_weight;
+        self.votes(&caller, proposal.id).set(&vote);
+    }
+
+    fn vote_in_referendum(
+        &self,
+        mut proposal: Proposal<Self::Api>,
+        relations: &ManagedVec<ManagedBuffer>,
+        vote_type: VoteType,
+        option: Option<usize>,
+    ) {
+        require!(self.call_value().all_esdt_transfers().is_empty(), ERROR_REFERENDUM_PAYMENT);
+
         let caller = self.blockchain().get_caller();
-        self.proposal_voters(proposal.id).insert(caller.clone());
-        self.voter_proposals(&caller).insert(proposal.id);
-        
+        let identity = match self.get_identity_by_address(caller.clone()) {
+            Some(identity) => identity,
+            None => sc_panic!(ERROR_IDENTITY_NOT_REGISTERED),
+        };
+        require!(self.is_school_member(identity.id, relations), ERROR_NOT_ELIGIBLE);
+        require!(self.referendum_voters(proposal.id).insert(identity.id), ERROR_IDENTITY_ALREADY_VOTED);
+
+        let weight = BigUint::from(1u64);
+        proposal.update_tally(&vote_type, option, &BigUint::zero(), &weight);
+        proposal.update_raw_tally(&vote_type, &BigUint::zero(), &weight);
+        self.proposals(proposal.id).set(&proposal);
+        self.votes(&caller, proposal.id).set(Vote {
+            vote_type,
+            weight: weight.clone(),
+            raw_weight: weight,
+            delegate: None,
+            option,
+        });
+    }
+
+    fn add_voter_amount(&self, voter: &ManagedAddress, proposal: &Proposal<Self::Api>, payment: EsdtTokenPayment) {
+        require!(
+            proposal.get_voting_token_weight(&payment.token_identifier, payment.token_nonce).is_some(),
+            ERROR_INVALID_PAYMENT,
+        );
+        require!(payment.amount > 0, ERROR_ZERO_PAYMENT);
+
+        self.proposal_voters(proposal.id).insert(voter.clone());
+        self.voter_proposals(voter).insert(proposal.id);
+
         // update the amount of tokens voted by the caller
+        let mut amounts = self.voters_amounts(voter, proposal.id).get();
+        merge_payment(&mut amounts, payment.clone());
+        self.voters_amounts(voter, proposal.id).set(&amounts);
+        self.escrow_payment(&payment);
+    }
+
+    // moves the whole weight of the caller's vote to another side
+    #[endpoint(changeVote)]
+    fn change_vote(&self, proposal_id: u64, vote_type: VoteType) {
+        let mut proposal = self.get_active_proposal(proposal_id);
+
+        let caller = self.blockchain().get_caller();
+        self.require_no_legacy_deposit(&caller, proposal_id);
+        require!(!self.votes(&caller, proposal_id).is_empty(), ERROR_NO_VOTE);
+
+        let mut vote = self.votes(&caller, proposal_id).get();
+        require!(vote.option.is_none(), ERROR_POLL_VOTE_CHANGE);
+        require!(vote.vote_type != vote_type, ERROR_SAME_VOTE_TYPE);
+
+        let zero = BigUint::zero();
+        proposal.update_tally(&vote.vote_type, None, &vote.weight, &zero);
+        proposal.update_raw_tally(&vote.vote_type, &vote.raw_weight, &zero);
+        if vote.delegate.is_some() {
+            // overriding the delegate, the delegated tokens now vote in person
+            let voter_tokens = self.get_voter_tokens(&caller, &proposal);
+            vote.weight = proposal.get_vote_weight(&voter_tokens);
+            vote.raw_weight = proposal.get_raw_vote_weight(&voter_tokens);
+            vote.delegate = None;
+        }
+        proposal.update_tally(&vote_type, None, &zero, &vote.weight);
+        proposal.update_raw_tally(&vote_type, &zero, &vote.raw_weight);
+        self.move_delegated_votes(&caller, &mut proposal, &vote_type);
+        self.proposals(proposal_id).set(&proposal);
+
+        vote.vote_type = vote_type;
+        self.votes(&caller, proposal_id).set(&vote);
+    }
+
+    // takes back part of the voted tokens, the vote weight is recomputed from what is left
+    #[endpoint(withdrawVote)]
+    fn withdraw_vote(&self, proposal_id: u64, token_id: TokenIdentifier, token_nonce: u64, amount: BigUint) {
+        let mut proposal = self.get_active_proposal(proposal_id);
+        require!(amount > 0, ERROR_ZERO_PAYMENT);
+
+        let caller = self.blockchain().get_caller();
+        self.require_no_legacy_deposit(&caller, proposal_id);
+        require!(!self.votes(&caller, proposal_id).is_empty(), ERROR_NO_VOTE);
+
+        let old_vec = self.voters_amounts(&caller, proposal_id).get();
         let mut new_vec: ManagedVec<EsdtTokenPayment> = ManagedVec::new();
-        let old_vec = self.voters_amounts(&caller, proposal.id).get();
         let mut found = false;
         for old_payment in old_vec.iter() {
-            if old_payment.token_identifier == payment.token_identifier && old_payment.token_nonce == payment.token_nonce {
-                new_vec.push(EsdtTokenPayment::new(
-                    payment.token_identifier.clone(),
-                    payment.token_nonce,
-                    &old_payment.amount + &payment.amount,
-                ));
+            if old_payment.token_identifier == token_id && old_payment.token_nonce == token_nonce {
+                require!(old_payment.amount >= amount, ERROR_NOT_ENOUGH_VOTED_AMOUNT);
+                if old_payment.amount > amount {
+                    new_vec.push(EsdtTokenPayment::new(token_id.clone(), token_nonce, &old_payment.amount - &amount));
+                }
                 found = true;
             } else {
                 new_vec.push(old_payment.clone());
             }
         }
-        if !found {
-            new_vec.push(payment.clone());
+        require!(found, ERROR_NOT_ENOUGH_VOTED_AMOUNT);
+
+        if new_vec.is_empty() {
+            self.voters_amounts(&caller, proposal_id).clear();
+            self.voter_proposals(&caller).swap_remove(&proposal_id);
+            self.proposal_voters(proposal_id).swap_remove(&caller);
+        } else {
+            self.voters_amounts(&caller, proposal_id).set(&new_vec);
+        }
+
+        let mut vote = self.votes(&caller, proposal_id).get();
+        let voter_tokens = self.get_voter_tokens(&caller, &proposal);
+        let new_weight = proposal.get_vote_weight(&voter_tokens);
+        let new_raw_weight = proposal.get_raw_vote_weight(&voter_tokens);
+        proposal.update_tally(&vote.vote_type, vote.option, &vote.weight, &new_weight);
+        proposal.update_raw_tally(&vote.vote_type, &vote.raw_weight, &new_raw_weight);
+        self.proposals(proposal_id).set(&proposal);
+
+        if new_vec.is_empty() && new_weight == 0 && self.delegators(&caller).is_empty() {
+            self.votes(&caller, proposal_id).clear();
+        } else {
+            vote.weight = new_weight;
+            vote.raw_weight = new_raw_weight;
+            self.votes(&caller, proposal_id).set(&vote);
         }
-        self.voters_amounts(&caller, proposal.id).set(&new_vec);
+
+        let payment = EsdtTokenPayment::new(token_id, token_nonce, amount);
+        self.release_payment(&payment);
+        self.send().direct_esdt(&caller, &payment.token_identifier, payment.token_nonce, &payment.amount);
+    }
+
+    // deposits made before votes were recorded have no known side, they can only be redeemed
+    fn require_no_legacy_deposit(&self, voter: &ManagedAddress, proposal_id: u64) {
+        require!(
+            !self.votes(voter, proposal_id).is_empty() || self.voters_amounts(voter, proposal_id).is_empty(),
+            ERROR_LEGACY_DEPOSIT,
+        );
+    }
+
+    // tokens deposited on the proposal plus the delegated and staked ones
+    fn get_voter_tokens(&self, voter: &ManagedAddress, proposal: &Proposal<Self::Api>) -> ManagedVec<EsdtTokenPayment> {
+        let mut tokens = self.voters_amounts(voter, proposal.id).get();
+        tokens.append_vec(self.get_delegated_tokens(voter, proposal));
+        tokens.append_vec(self.get_staked_tokens(voter, proposal.voting_end));
+
+        tokens
+    }
+
+    fn get_active_proposal(&self, proposal_id: u64) -> Proposal<Self::Api> {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.proposals(proposal_id).is_empty(), ERROR_PROPOSAL_NOT_FOUND);
+
+        let proposal = self.proposals(proposal_id).get();
+        let pstat = self.get_proposal_status(&proposal);
+        require!(pstat == ProposalStatus::Active, ERROR_PROPOSAL_NOT_ACTIVE);
+
+        proposal
     }
 
     #[endpoint(redeem)]
     fn redeem(&self, proposal_id: u64) {
         let proposal = self.proposals(proposal_id).get();
         let pstat = self.get_proposal_status(&proposal);
-        require!(
-            pstat == ProposalStatus::Succeeded || pstat == ProposalStatus::Defeated || pstat == ProposalStatus::Executed,
-            ERROR_VOTING_PERIOD_NOT_ENDED,
-        );
+        require!(self.is_voting_closed(&pstat), ERROR_VOTING_PERIOD_NOT_ENDED);
 
         let caller = self.blockchain().get_caller();
         let payments = self.voters_amounts(&caller, proposal_id).take();
@@ -164,6 +807,7 @@ common::config::ConfigModule
         self.proposal_voters(proposal_id).swap_remove(&caller);
         require!(!payments.is_empty(), ERROR_NOTHING_TO_REDEEM);
 
+        self.release_payments(&payments);
         self.send().direct_multi(&caller, &payments);
     }
 
@@ -174,44 +818,152 @@ common::config::ConfigModule
 
         let mut proposal = self.proposals(proposal_id).get();
         let pstat = self.get_proposal_status(&proposal);
-        require!(pstat == ProposalStatus::Succeeded, ERROR_PROPOSAL_NOT_SUCCEEDED);
+        require!(
+            pstat == ProposalStatus::Succeeded || pstat == ProposalStatus::ExecutionFailed,
+            ERROR_PROPOSAL_NOT_SUCCEEDED,
+        );
+        require!(self.pending_actions(proposal_id).get() == 0, ERROR_EXECUTION_IN_PROGRESS);
 
-        self.execute_proposal(&proposal);
-        proposal.was_executed = true;
+        proposal.failed_action = None;
+        proposal.was_executed = self.execute_proposal(&proposal);
         self.proposals(proposal_id).set(&proposal);
     }
 
-    fn execute_proposal(&self, proposal: &Proposal<Self::Api>) {
-        match proposal.proposal_data.clone() {
-            ProposalType::Nothing => return,
+    // the main DAO can stop a proposal until it becomes executable, the board does it through `proposeVetoProposal`
+    #[endpoint(vetoProposal)]
+    fn veto_proposal_endpoint(&self, proposal_id: u64) {
+        let caller = self.blockchain().get_caller();
+        require!(caller == self.main_dao().get(), ERROR_ONLY_MAIN_DAO);
 
-            ProposalType::NewTransfer(transfer_proposal) => {
-                for action in transfer_proposal.actions.iter() {
-                    self.execute_action(&action).unwrap();
+        self.veto_proposal(proposal_id, true);
+    }
+
+    // returns true if the proposal was fully executed in this transaction
+    fn execute_proposal(&self, proposal: &Proposal<Self::Api>) -> bool {
+        match proposal.proposal_data.clone() {
+            ProposalType::Nothing | ProposalType::Poll(_) => true,
+
+            ProposalType::NewTransfer(transfer_proposal) |
+            ProposalType::OptimisticTransfer(transfer_proposal) => {
+                // spending is checked against the budget again and charged to the current period
+                let payments = self.get_remaining_payments(proposal.id, &transfer_proposal);
+                let totals = self.sum_per_token(&payments);
+                self.require_free_balances(&payments);
+                self.require_within_budget(&transfer_proposal.category, &totals);
+                let budget_period_start = self.get_current_budget_period();
+                self.charge_budget(&transfer_proposal.category, budget_period_start, &totals);
+
+                match transfer_proposal.execution_mode {
+                    // any failing call reverts the whole transaction, so the proposal stays executable
+                    ExecutionMode::Atomic => {
+                        for (action_index, action) in transfer_proposal.actions.iter().enumerate() {
+                            self.execute_action(&action);
+                            self.record_outflows(proposal.id, action_index, &action.dest_address, &action.payments);
+                        }
+
+                        true
+                    },
+                    ExecutionMode::PerAction => {
+                        let mut pending_actions = 0;
+                        for (action_index, action) in transfer_proposal.actions.iter().enumerate() {
+                            if self.executed_actions(proposal.id).contains(&action_index) {
+                                continue;
+                            }
+
+                            self.execute_action_async(proposal.id, action_index, &action, budget_period_start);
+                            pending_actions += 1;
+                        }
+                        self.pending_actions(proposal.id).set(pending_actions);
+
+                        pending_actions == 0
+                    },
                 }
             },
-        };
+
+            ProposalType::BoardAction(action) => {
+                self.execute_board_action(action);
+
+                true
+            },
+        }
+    }
+
+    // payments of the actions still to run
+    fn get_remaining_payments(
+        &self,
+        proposal_id: u64,
+        transfer_proposal: &TransferProposal<Self::Api>,
+    ) -> ManagedVec<EgldOrEsdtTokenPayment> {
+        let mut payments: ManagedVec<EgldOrEsdtTokenPayment> = ManagedVec::new();
+        for (action_index, action) in transfer_proposal.actions.iter().enumerate() {
+            if self.executed_actions(proposal_id).contains(&action_index) {
+                continue;
+            }
+
+            payments.append_vec(action.payments.clone());
+        }
+
+        payments
+    }
+
+    fn execute_action(&self, action: &Action<Self::Api>) {
+        self.tx()
+            .to(&action.dest_address)
+            .payment(action.get_payment())
+            .gas(action.gas_limit)
+            .raw_call(action.endpoint_name.clone())
+            .arguments_raw(ManagedArgBuffer::from(action.arguments.clone()))
+            .sync_call();
+    }
+
+    fn execute_action_async(&self, proposal_id: u64, action_index: usize, action: &Action<Self::Api>, budget_period_start: u64) {
+        self.tx()
+            .to(&action.dest_address)
+            .payment(action.get_payment())
+            .gas(action.gas_limit)
+            .raw_call(action.endpoint_name.clone())
+            .arguments_raw(ManagedArgBuffer::from(action.arguments.clone()))
+            .callback(self.callbacks().execute_action_callback(proposal_id, action_index, budget_period_start))
+            .gas_for_callback(CALLBACK_GAS_LIMIT)
+            .register_promise();
     }
 
-    fn execute_action(&self, action: &Action<Self::Api>) -> Result<(), &'static [u8]> {
-        let payment =
-            EgldOrEsdtTokenPayment::new(action.payment_token.clone(), 0, action.payment_amount.clone());
-        if action.payment_amount > 0 {
-            self.send()
-                .contract_call::<()>(action.dest_address.clone(), action.endpoint_name.clone())
-                .with_egld_or_single_esdt_transfer(payment)
-                .with_raw_arguments(ManagedArgBuffer::from(action.arguments.clone()))
-                .with_gas_limit(action.gas_limit)
-                .transfer_execute();
+    #[promises_callback]
+    fn execute_action_callback(
+        &self,
+        proposal_id: u64,
+        action_index: usize,
+        budget_period_start: u64,
+        #[call_result] result: ManagedAsyncCallResult<IgnoreValue>,
+    ) {
+        let mut proposal = self.proposals(proposal_id).get();
+        let succeeded = matches!(result, ManagedAsyncCallResult::Ok(_));
+        if let Some(transfer_proposal) = proposal.proposal_data.get_transfer_proposal() {
+            let action = transfer_proposal.actions.get(action_index);
+            if succeeded {
+                self.record_outflows(proposal_id, action_index, &action.dest_address, &action.payments);
+            } else {
+                self.refund_budget(&transfer_proposal.category, budget_period_start, &action.payments);
+            }
+        }
+
+        if succeeded {
+            self.executed_actions(proposal_id).insert(action_index);
         } else {
-            self.send()
-                .contract_call::<()>(action.dest_address.clone(), action.endpoint_name.clone())
-                .with_raw_arguments(ManagedArgBuffer::from(action.arguments.clone()))
-                .with_gas_limit(action.gas_limit)
-                .transfer_execute();
+            // keep the first failing action
+            let failed_action = match proposal.failed_action {
+                Some(index) if index < action_index => index,
+                _ => action_index,
+            };
+            proposal.failed_action = Some(failed_action);
         }
 
-        Result::Ok(())
+        let pending_actions = self.pending_actions(proposal_id).get() - 1;
+        self.pending_actions(proposal_id).set(pending_actions);
+        if pending_actions == 0 && proposal.failed_action.is_none() {
+            proposal.was_executed = true;
+        }
+        self.proposals(proposal_id).set(&proposal);
     }
 
     // proxies
@@ -1,4 +1,4 @@
-use crate::common::{board_config::*, errors::*};
+use crate::common::{board_config::*, config::{ProposerMode, QuorumMode, State, VoteCurve}, consts::MAX_PERCENTAGE, errors::*};
 
 multiversx_sc::imports!();
 
@@ -7,6 +7,8 @@ pub trait MultisigModule:
 crate::common::board_config::BoardConfigModule
 + crate::common::config::ConfigModule
 + crate::common::school_config::SchoolConfigModule
++ crate::common::staking_config::StakingConfigModule
++ crate::common::treasury_config::TreasuryConfigModule
 {
     #[endpoint]
     fn sign(&self, action_id: usize) {
@@ -50,6 +52,7 @@ crate::common::board_config::BoardConfigModule
     fn propose_action(&self, action: BoardAction<Self::Api>) -> usize {
         let caller = self.blockchain().get_caller();
         require!(self.board_members().contains(&caller), ERROR_ONLY_BOARD_MEMBERS);
+        self.require_valid_board_action(&action);
 
         let action_id = self.action_mapper().push(&action);
         self.action_signers(action_id).insert(caller);
@@ -59,36 +62,26 @@ crate::common::board_config::BoardConfigModule
 
     #[endpoint(proposeAddBoardMember)]
     fn propose_add_board_member(&self, board_member_address: ManagedAddress) -> usize {
-        require!(!self.board_members().contains(&board_member_address), ERROR_ALREADY_BOARD_MEMBER);
-
         self.propose_action(BoardAction::AddBoardMember(board_member_address))
     }
 
     #[endpoint(proposeRemoveUser)]
     fn propose_remove_user(&self, user_address: ManagedAddress) -> usize {
-        require!(self.board_members().contains(&user_address), ERROR_NOT_BOARD_MEMBER);
-
         self.propose_action(BoardAction::RemoveBoardMember(user_address))
     }
 
     #[endpoint(proposeChangeBoardQuorum)]
     fn propose_change_board_quorum(&self, new_quorum: usize) -> usize {
-        require!(new_quorum > 0, ERROR_ZERO_VALUE);
-
         self.propose_action(BoardAction::ChangeBoardQuorum(new_quorum))
     }
 
     #[endpoint(proposeChangeQuorum)]
     fn propose_change_quorum(&self, new_quorum: BigUint) -> usize {
-        require!(new_quorum > 0, ERROR_ZERO_VALUE);
-
         self.propose_action(BoardAction::ChangeQuorum(new_quorum))
     }
 
     #[endpoint(proposeChangeVotingPeriod)]
     fn propose_change_vorint_period(&self, new_period: u64) -> usize {
-        require!(new_period > 0, ERROR_ZERO_VALUE);
-
         self.propose_action(BoardAction::ChangeVotingPeriod(new_period))
     }
 
@@ -98,26 +91,157 @@ crate::common::board_config::BoardConfigModule
         token: TokenIdentifier,
         weight: BigUint,
     ) -> usize {
-        require!(!self.voting_tokens().contains_key(&token), ERROR_TOKEN_ALREADY_EXISTS);
-        require!(weight > 0, ERROR_ZERO_VALUE);
-
         self.propose_action(BoardAction::AddVotingToken(token, weight))
     }
 
     #[endpoint(proposeRemoveVotingToken)]
     fn propose_remove_voting_token(&self, token: TokenIdentifier) -> usize {
-        require!(self.voting_tokens().contains_key(&token), ERROR_TOKEN_NOT_FOUND);
-
         self.propose_action(BoardAction::RemoveVotingToken(token))
     }
 
     #[endpoint(proposeChangeTaxAmount)]
     fn propose_change_tax_amount(&self, new_tax_amount: BigUint) -> usize {
-        require!(new_tax_amount > 0, ERROR_ZERO_VALUE);
-
         self.propose_action(BoardAction::ChangeTaxAmount(new_tax_amount))
     }
 
+    #[endpoint(proposeChangeExecutionDelay)]
+    fn propose_change_execution_delay(&self, new_delay: u64) -> usize {
+        self.propose_action(BoardAction::ChangeExecutionDelay(new_delay))
+    }
+
+    #[endpoint(proposeChangeGracePeriod)]
+    fn propose_change_grace_period(&self, new_grace_period: u64) -> usize {
+        self.propose_action(BoardAction::ChangeGracePeriod(new_grace_period))
+    }
+
+    #[endpoint(proposeVetoProposal)]
+    fn propose_veto_proposal(&self, proposal_id: u64) -> usize {
+        self.propose_action(BoardAction::VetoProposal(proposal_id))
+    }
+
+    #[endpoint(proposeChangeProposalDeposit)]
+    fn propose_change_proposal_deposit(&self, new_deposit: BigUint) -> usize {
+        self.propose_action(BoardAction::ChangeProposalDeposit(new_deposit))
+    }
+
+    #[endpoint(proposeChangeDepositRefundTurnout)]
+    fn propose_change_deposit_refund_turnout(&self, new_turnout: BigUint) -> usize {
+        self.propose_action(BoardAction::ChangeDepositRefundTurnout(new_turnout))
+    }
+
+    #[endpoint(proposeChangeMaxOpenProposals)]
+    fn propose_change_max_open_proposals(&self, new_max: usize) -> usize {
+        self.propose_action(BoardAction::ChangeMaxOpenProposals(new_max))
+    }
+
+    #[endpoint(proposeChangeProposerMode)]
+    fn propose_change_proposer_mode(&self, new_mode: ProposerMode) -> usize {
+        self.propose_action(BoardAction::ChangeProposerMode(new_mode))
+    }
+
+    #[endpoint(proposeChangeProposerThreshold)]
+    fn propose_change_proposer_threshold(&self, new_threshold: BigUint) -> usize {
+        self.propose_action(BoardAction::ChangeProposerThreshold(new_threshold))
+    }
+
+    #[endpoint(proposeChangeVotingDelay)]
+    fn propose_change_voting_delay(&self, new_delay: u64) -> usize {
+        self.propose_action(BoardAction::ChangeVotingDelay(new_delay))
+    }
+
+    #[endpoint(proposeChangeParticipationQuorum)]
+    fn propose_change_participation_quorum(&self, new_quorum: BigUint) -> usize {
+        self.propose_action(BoardAction::ChangeParticipationQuorum(new_quorum))
+    }
+
+    #[endpoint(proposeChangeApprovalThreshold)]
+    fn propose_change_approval_threshold(&self, new_threshold: u64) -> usize {
+        self.propose_action(BoardAction::ChangeApprovalThreshold(new_threshold))
+    }
+
+    // a zero multiplier removes the lock duration
+    #[endpoint(proposeSetLockMultiplier)]
+    fn propose_set_lock_multiplier(&self, lock_duration: u64, multiplier: u64) -> usize {
+        self.propose_action(BoardAction::SetLockMultiplier(lock_duration, multiplier))
+    }
+
+    // a zero weight falls back to the collection weight
+    #[endpoint(proposeSetVotingTokenNonceWeight)]
+    fn propose_set_voting_token_nonce_weight(&self, token: TokenIdentifier, nonce: u64, weight: BigUint) -> usize {
+        self.propose_action(BoardAction::SetVotingTokenNonceWeight(token, nonce, weight))
+    }
+
+    #[endpoint(proposeChangeReferendumQuorum)]
+    fn propose_change_referendum_quorum(&self, new_quorum: u64) -> usize {
+        self.propose_action(BoardAction::ChangeReferendumQuorum(new_quorum))
+    }
+
+    #[endpoint(proposeChangeBindingReferendums)]
+    fn propose_change_binding_referendums(&self, binding: bool) -> usize {
+        self.propose_action(BoardAction::ChangeBindingReferendums(binding))
+    }
+
+    #[endpoint(proposeChangeQuorumMode)]
+    fn propose_change_quorum_mode(&self, new_mode: QuorumMode) -> usize {
+        self.propose_action(BoardAction::ChangeQuorumMode(new_mode))
+    }
+
+    #[endpoint(proposeSetVotingTokenSupply)]
+    fn propose_set_voting_token_supply(&self, token: TokenIdentifier, supply: BigUint) -> usize {
+        self.propose_action(BoardAction::SetVotingTokenSupply(token, supply))
+    }
+
+    // the cap is only used by CappedLinear
+    #[endpoint(proposeSetVoteCurve)]
+    fn propose_set_vote_curve(&self, token: TokenIdentifier, curve: VoteCurve, cap: BigUint) -> usize {
+        self.propose_action(BoardAction::SetVoteCurve(token, curve, cap))
+    }
+
+    #[endpoint(proposeChangeVotingPeriodBounds)]
+    fn propose_change_voting_period_bounds(&self, min_period: u64, max_period: u64) -> usize {
+        self.propose_action(BoardAction::ChangeVotingPeriodBounds(min_period, max_period))
+    }
+
+    // a zero multiplier removes the bracket
+    #[endpoint(proposeSetUrgencyQuorumMultiplier)]
+    fn propose_set_urgency_quorum_multiplier(&self, max_period: u64, multiplier: u64) -> usize {
+        self.propose_action(BoardAction::SetUrgencyQuorumMultiplier(max_period, multiplier))
+    }
+
+    #[endpoint(proposeChangeOptimisticVetoThreshold)]
+    fn propose_change_optimistic_veto_threshold(&self, new_threshold: BigUint) -> usize {
+        self.propose_action(BoardAction::ChangeOptimisticVetoThreshold(new_threshold))
+    }
+
+    // a zero cap forbids spending the token optimistically
+    #[endpoint(proposeSetOptimisticSpendingCap)]
+    fn propose_set_optimistic_spending_cap(&self, token: EgldOrEsdtTokenIdentifier, cap: BigUint) -> usize {
+        self.propose_action(BoardAction::SetOptimisticSpendingCap(token, cap))
+    }
+
+    // a zero cap removes the token from the category, the category goes away with its last cap
+    #[endpoint(proposeSetBudgetCap)]
+    fn propose_set_budget_cap(&self, category: ManagedBuffer, token: EgldOrEsdtTokenIdentifier, cap: BigUint) -> usize {
+        self.propose_action(BoardAction::SetBudgetCap(category, token, cap))
+    }
+
+    // takes effect when the running period ends, a zero length makes a single period that never ends
+    #[endpoint(proposeChangeBudgetPeriod)]
+    fn propose_change_budget_period(&self, period_length: u64) -> usize {
+        self.propose_action(BoardAction::ChangeBudgetPeriod(period_length))
+    }
+
+    // a zero reserve removes it, needs the reserve quorum of signers
+    #[endpoint(proposeSetMinReserve)]
+    fn propose_set_min_reserve(&self, token: EgldOrEsdtTokenIdentifier, nonce: u64, min_reserve: BigUint) -> usize {
+        self.propose_action(BoardAction::SetMinReserve(token, nonce, min_reserve))
+    }
+
+    #[endpoint(proposeChangeReserveBoardQuorum)]
+    fn propose_change_reserve_board_quorum(&self, new_quorum: usize) -> usize {
+        self.propose_action(BoardAction::ChangeReserveBoardQuorum(new_quorum))
+    }
+
     #[endpoint(performAction)]
     fn perform_action_endpoint(&self, action_id: usize) {
         let caller = self.blockchain().get_caller();
@@ -134,6 +258,14 @@ crate::common::board_config::BoardConfigModule
         let action = self.action_mapper().get(action_id);
         self.action_mapper().clear_entry_unchecked(action_id);
         self.action_signers(action_id).clear();
+        self.execute_board_action(action);
+    }
+
+    // shared by the board multisig and by token holder proposals
+    fn execute_board_action(&self, action: BoardAction<Self::Api>) {
+        // the configuration may have changed since the action was proposed
+        self.require_valid_board_action(&action);
+
         match action {
             BoardAction::Nothing=>return,
             BoardAction::AddBoardMember(board_member_address) => {
@@ -156,13 +288,242 @@ crate::common::board_config::BoardConfigModule
             },
             BoardAction::RemoveVotingToken(token) => {
                 self.voting_tokens().remove(&token);
+                self.voting_token_nonce_weights(&token).clear();
+                self.voting_token_supply().remove(&token);
+                self.voting_token_curves().remove(&token);
+                self.voting_token_caps().remove(&token);
                 if self.voting_tokens().is_empty() {
-                    self.set_state_inactive();
+                    self.state().set(State::Inactive);
                 }
             },
             BoardAction::ChangeTaxAmount(new_tax_amount) => {
                 self.tax_amount().set(new_tax_amount);
             },
+            BoardAction::ChangeExecutionDelay(new_delay) => {
+                self.execution_delay().set(new_delay);
+            },
+            BoardAction::ChangeGracePeriod(new_grace_period) => {
+                self.grace_period().set(new_grace_period);
+            },
+            BoardAction::VetoProposal(proposal_id) => {
+                self.veto_proposal(proposal_id, false);
+            },
+            BoardAction::ChangeProposalDeposit(new_deposit) => {
+                self.proposal_deposit_amount().set(new_deposit);
+            },
+            BoardAction::ChangeDepositRefundTurnout(new_turnout) => {
+                self.deposit_refund_turnout().set(new_turnout);
+            },
+            BoardAction::ChangeMaxOpenProposals(new_max) => {
+                self.max_open_proposals().set(new_max);
+            },
+            BoardAction::ChangeProposerMode(new_mode) => {
+                self.proposer_mode().set(new_mode);
+            },
+            BoardAction::ChangeProposerThreshold(new_threshold) => {
+                self.proposer_threshold().set(new_threshold);
+            },
+            BoardAction::ChangeVotingDelay(new_delay) => {
+                self.voting_delay().set(new_delay);
+            },
+            BoardAction::ChangeParticipationQuorum(new_quorum) => {
+                self.participation_quorum().set(new_quorum);
+            },
+            BoardAction::ChangeApprovalThreshold(new_threshold) => {
+                self.approval_threshold().set(new_threshold);
+            },
+            BoardAction::SetLockMultiplier(lock_duration, multiplier) => {
+                if multiplier == 0 {
+                    self.lock_multipliers().remove(&lock_duration);
+                } else {
+                    self.lock_multipliers().insert(lock_duration, multiplier);
+                }
+            },
+            BoardAction::SetVotingTokenNonceWeight(token, nonce, weight) => {
+                if weight == 0 {
+                    self.voting_token_nonce_weights(&token).remove(&nonce);
+                } else {
+                    self.voting_token_nonce_weights(&token).insert(nonce, weight);
+                }
+            },
+            BoardAction::ChangeReferendumQuorum(new_quorum) => {
+                self.referendum_quorum().set(new_quorum);
+            },
+            BoardAction::ChangeQuorumMode(new_mode) => {
+                self.quorum_mode().set(new_mode);
+            },
+            BoardAction::SetVotingTokenSupply(token, supply) => {
+                if supply == 0 {
+                    self.voting_token_supply().remove(&token);
+                } else {
+                    self.voting_token_supply().insert(token, supply);
+                }
+            },
+            BoardAction::SetVoteCurve(token, curve, cap) => {
+                self.voting_token_curves().insert(token.clone(), curve);
+                self.voting_token_caps().insert(token, cap);
+            },
+            BoardAction::ChangeVotingPeriodBounds(min_period, max_period) => {
+                self.min_voting_period().set(min_period);
+                self.max_voting_period().set(max_period);
+            },
+            BoardAction::ChangeOptimisticVetoThreshold(new_threshold) => {
+                self.optimistic_veto_threshold().set(new_threshold);
+            },
+            BoardAction::SetOptimisticSpendingCap(token, cap) => {
+                if cap == 0 {
+                    self.optimistic_spending_caps().remove(&token);
+                } else {
+                    self.optimistic_spending_caps().insert(token, cap);
+                }
+            },
+            BoardAction::SetUrgencyQuorumMultiplier(max_period, multiplier) => {
+                if multiplier == 0 {
+                    self.urgency_quorum_multipliers().remove(&max_period);
+                } else {
+                    self.urgency_quorum_multipliers().insert(max_period, multiplier);
+                }
+            },
+            BoardAction::SetBudgetCap(category, token, cap) => {
+                if cap == 0 {
+                    self.budget_caps(&category).remove(&token);
+                    if self.budget_caps(&category).is_empty() {
+                        self.budget_categories().swap_remove(&category);
+                    }
+                } else {
+                    self.budget_caps(&category).insert(token, cap);
+                    self.budget_categories().insert(category);
+                }
+            },
+            BoardAction::ChangeBudgetPeriod(period_length) => {
+                self.change_budget_period_length(period_length);
+            },
+            BoardAction::SetMinReserve(token, nonce, min_reserve) => {
+                self.min_reserve(&token, nonce).set(min_reserve);
+            },
+            BoardAction::ChangeReserveBoardQuorum(new_quorum) => {
+                self.reserve_board_quorum().set(new_quorum);
+            },
+            BoardAction::ChangeBindingReferendums(binding) => {
+                self.binding_referendums().set(binding);
+            },
+        };
+    }
+
+    fn require_valid_board_action(&self, action: &BoardAction<Self::Api>) {
+        match action {
+            BoardAction::Nothing => {},
+            BoardAction::AddBoardMember(board_member_address) => {
+                require!(!self.board_members().contains(board_member_address), ERROR_ALREADY_BOARD_MEMBER);
+            },
+            BoardAction::RemoveBoardMember(board_member_address) => {
+                require!(self.board_members().contains(board_member_address), ERROR_NOT_BOARD_MEMBER);
+                require!(self.board_members().len() > self.reserve_board_quorum().get(), ERROR_BELOW_RESERVE_QUORUM);
+            },
+            BoardAction::ChangeBoardQuorum(new_quorum) => {
+                require!(*new_quorum > 0, ERROR_ZERO_VALUE);
+            },
+            BoardAction::ChangeQuorum(new_quorum) => {
+                require!(*new_quorum > 0, ERROR_ZERO_VALUE);
+                self.require_valid_quorum_value(new_quorum, self.quorum_mode().get());
+            },
+            BoardAction::ChangeVotingPeriod(new_voting_period) => {
+                require!(*new_voting_period > 0, ERROR_ZERO_VALUE);
+            },
+            BoardAction::AddVotingToken(token, weight) => {
+                require!(!self.voting_tokens().contains_key(token), ERROR_TOKEN_ALREADY_EXISTS);
+                require!(*weight > 0, ERROR_ZERO_VALUE);
+                require!(
+                    self.quorum_mode().get() != QuorumMode::SupplyPercentage || self.voting_token_supply().contains_key(token),
+                    ERROR_MISSING_TOKEN_SUPPLY,
+                );
+            },
+            BoardAction::RemoveVotingToken(token) => {
+                require!(self.voting_tokens().contains_key(token), ERROR_TOKEN_NOT_FOUND);
+            },
+            BoardAction::ChangeTaxAmount(new_tax_amount) => {
+                require!(*new_tax_amount > 0, ERROR_ZERO_VALUE);
+            },
+            BoardAction::ChangeExecutionDelay(_) => {},
+            BoardAction::ChangeGracePeriod(_) => {},
+            BoardAction::VetoProposal(proposal_id) => {
+                require!(!self.proposals(*proposal_id).is_empty(), ERROR_PROPOSAL_NOT_FOUND);
+
+                let proposal = self.proposals(*proposal_id).get();
+                require!(self.can_be_vetoed(&self.get_proposal_status(&proposal)), ERROR_PROPOSAL_CANNOT_BE_VETOED);
+                require!(self.can_board_veto(&proposal), ERROR_BOARD_CANNOT_VETO);
+            },
+            BoardAction::ChangeProposalDeposit(_) => {},
+            BoardAction::ChangeDepositRefundTurnout(_) => {},
+            BoardAction::ChangeMaxOpenProposals(_) => {},
+            BoardAction::ChangeProposerMode(_) => {},
+            BoardAction::ChangeProposerThreshold(_) => {},
+            BoardAction::ChangeVotingDelay(_) => {},
+            BoardAction::ChangeParticipationQuorum(new_quorum) => {
+                self.require_valid_quorum_value(new_quorum, self.quorum_mode().get());
+            },
+            BoardAction::ChangeApprovalThreshold(new_threshold) => {
+                require!(*new_threshold <= MAX_PERCENTAGE, ERROR_INVALID_PERCENTAGE);
+            },
+            BoardAction::SetLockMultiplier(lock_duration, multiplier) => {
+                require!(*lock_duration > 0, ERROR_ZERO_VALUE);
+                // staking never weighs less than the plain deposit
+                require!(*multiplier == 0 || *multiplier >= MAX_PERCENTAGE, ERROR_INVALID_PERCENTAGE);
+            },
+            BoardAction::SetVotingTokenNonceWeight(token, nonce, _) => {
+                require!(self.voting_tokens().contains_key(token), ERROR_TOKEN_NOT_FOUND);
+                require!(*nonce > 0, ERROR_ZERO_VALUE);
+            },
+            BoardAction::ChangeReferendumQuorum(_) => {},
+            BoardAction::ChangeQuorumMode(new_mode) => {
+                self.require_valid_quorum_value(&self.quorum().get(), *new_mode);
+                self.require_valid_quorum_value(&self.participation_quorum().get(), *new_mode);
+                self.require_valid_quorum_value(&self.optimistic_veto_threshold().get(), *new_mode);
+                if *new_mode == QuorumMode::SupplyPercentage {
+                    for token in self.voting_tokens().keys() {
+                        require!(self.voting_token_supply().contains_key(&token), ERROR_MISSING_TOKEN_SUPPLY);
+                    }
+                }
+            },
+            // the supply can be set before the token is added, zero removes it
+            BoardAction::SetVotingTokenSupply(token, supply) => {
+                require!(
+                    *supply > 0 ||
+                        self.quorum_mode().get() != QuorumMode::SupplyPercentage ||
+                        !self.voting_tokens().contains_key(token),
+                    ERROR_MISSING_TOKEN_SUPPLY,
+                );
+            },
+            BoardAction::SetVoteCurve(token, curve, cap) => {
+                require!(self.voting_tokens().contains_key(token), ERROR_TOKEN_NOT_FOUND);
+                require!(*curve != VoteCurve::CappedLinear || *cap > 0, ERROR_ZERO_CAP);
+            },
+            BoardAction::ChangeVotingPeriodBounds(min_period, max_period) => {
+                require!(*max_period == 0 || (*min_period > 0 && min_period <= max_period), ERROR_INVALID_VOTING_PERIOD);
+            },
+            BoardAction::SetUrgencyQuorumMultiplier(max_period, multiplier) => {
+                require!(*max_period > 0, ERROR_ZERO_VALUE);
+                // urgency only ever raises the quorum
+                require!(*multiplier == 0 || *multiplier >= MAX_PERCENTAGE, ERROR_INVALID_PERCENTAGE);
+            },
+            BoardAction::ChangeOptimisticVetoThreshold(new_threshold) => {
+                self.require_valid_quorum_value(new_threshold, self.quorum_mode().get());
+            },
+            BoardAction::SetOptimisticSpendingCap(_, _) => {},
+            BoardAction::SetBudgetCap(category, _, _) => {
+                require!(!category.is_empty(), ERROR_MISSING_BUDGET_CATEGORY);
+            },
+            BoardAction::ChangeBudgetPeriod(_) => {},
+            BoardAction::SetMinReserve(_, _, _) => {},
+            BoardAction::ChangeReserveBoardQuorum(new_quorum) => {
+                require!(*new_quorum <= self.board_members().len(), ERROR_BELOW_RESERVE_QUORUM);
+            },
+            BoardAction::ChangeBindingReferendums(_) => {},
         };
     }
+
+    // percentage modes read the quorums in basis points
+    fn require_valid_quorum_value(&self, value: &BigUint, mode: QuorumMode) {
+        require!(mode == QuorumMode::Absolute || *value <= MAX_PERCENTAGE, ERROR_INVALID_PERCENTAGE);
+    }
 }
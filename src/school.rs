@@ -82,6 +82,7 @@ school_config::SchoolConfigModule
         keys.push(MARK_KEY.into());
         keys.push(ABSENCE_KEY.into());
         keys.push(TAX_VALIDITY_KEY.into());
+        self.identity_relations(student_id).set(ManagedBuffer::from(STUDENT_RELATION));
         self.digital_identity_contract_proxy()
             .contract(self.digital_identity_sc().get())
             .request_link(
@@ -165,6 +166,7 @@ school_config::SchoolConfigModule
         } else {
             EMPLOYEE_RELATION
         };
+        self.identity_relations(employee_id).set(ManagedBuffer::from(relation));
         self.digital_identity_contract_proxy()
             .contract(self.digital_identity_sc().get())
             .request_link(
@@ -211,6 +213,20 @@ school_config::SchoolConfigModule
         employee_id
     }
 
+    // records the relation of employees hired before relations were kept, or corrects it
+    #[endpoint(setEmployeeRelation)]
+    fn set_employee_relation(&self, employee_identity_id: u64, is_teacher: bool) {
+        self.only_board_members();
+        require!(self.get_employee_id_by_identity_id(employee_identity_id).is_some(), ERROR_EMPLOYEE_NOT_FOUND);
+
+        let relation = if is_teacher {
+            TEACHER_RELATION
+        } else {
+            EMPLOYEE_RELATION
+        };
+        self.identity_relations(employee_identity_id).set(ManagedBuffer::from(relation));
+    }
+
     #[endpoint(fireEmployee)]
     fn fire_employee(&self, employee_id: u64) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
@@ -380,6 +396,7 @@ school_config::SchoolConfigModule
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
         self.only_board_members();
 
+        self.identity_relations(id).clear();
         self.digital_identity_contract_proxy()
             .contract(self.digital_identity_sc().get())
             .remove_identity_link(self.identity_id().get(), id)
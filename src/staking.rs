@@ -0,0 +1,116 @@
+multiversx_sc::imports!();
+
+use crate::common::errors::*;
+use crate::common::consts::{MAX_PERCENTAGE, ONE};
+use crate::common::config::{self, merge_payment, State};
+use crate::common::staking_config::{self, *};
+use crate::common::board_config;
+use crate::common::treasury_config;
+
+#[multiversx_sc::module]
+pub trait StakingModule:
+staking_config::StakingConfigModule
++treasury_config::TreasuryConfigModule
++board_config::BoardConfigModule
++config::ConfigModule
+{
+    // locks voting tokens for one of the allowed durations, calling it without payment only extends the lock
+    #[payable("*")]
+    #[endpoint(stake)]
+    fn stake(&self, lock_duration: u64) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let opt_multiplier = self.lock_multipliers().get(&lock_duration);
+        require!(opt_multiplier.is_some(), ERROR_INVALID_LOCK_DURATION);
+
+        let payments = self.call_value().all_esdt_transfers().clone_value();
+        for payment in payments.iter() {
+            require!(self.voting_tokens().contains_key(&payment.token_identifier), ERROR_INVALID_PAYMENT);
+            require!(payment.amount > 0, ERROR_ZERO_PAYMENT);
+        }
+
+        let caller = self.blockchain().get_caller();
+        let mut position = if self.stakes(&caller).is_empty() {
+            require!(!payments.is_empty(), ERROR_ZERO_PAYMENT);
+            StakePosition {
+                payments: ManagedVec::new(),
+                unlock_timestamp: 0,
+                multiplier: 0,
+            }
+        } else {
+            self.stakes(&caller).get()
+        };
+
+        // the whole position is locked again, a lock ending sooner would lend the added tokens the old multiplier
+        let unlock_timestamp = self.blockchain().get_block_timestamp() + lock_duration;
+        require!(position.can_relock(unlock_timestamp), ERROR_SHORTER_LOCK);
+        position.unlock_timestamp = unlock_timestamp;
+        position.multiplier = opt_multiplier.unwrap();
+
+        for payment in payments.iter() {
+            merge_payment(&mut position.payments, payment.clone());
+            self.escrow_payment(&payment);
+
+            let total_staked = self.total_staked().get(&payment.token_identifier).unwrap_or_default();
+            self.total_staked().insert(payment.token_identifier.clone(), total_staked + &payment.amount);
+        }
+
+        self.stakes(&caller).set(&position);
+        self.stakers().insert(caller);
+    }
+
+    #[endpoint(unstake)]
+    fn unstake(&self) {
+        let caller = self.blockchain().get_caller();
+        require!(!self.stakes(&caller).is_empty(), ERROR_NO_STAKE);
+
+        let position = self.stakes(&caller).get();
+        require!(self.blockchain().get_block_timestamp() >= position.unlock_timestamp, ERROR_STAKE_LOCKED);
+
+        for payment in position.payments.iter() {
+            let total_staked = self.total_staked().get(&payment.token_identifier).unwrap_or_default();
+            self.total_staked().insert(payment.token_identifier.clone(), total_staked - &payment.amount);
+        }
+        self.stakes(&caller).clear();
+        self.stakers().swap_remove(&caller);
+        self.release_payments(&position.payments);
+        self.send().direct_multi(&caller, &position.payments);
+    }
+
+    // staked tokens scaled by what is left of their lock at the end of the vote, they only count while locked until then
+    fn get_staked_tokens(&self, staker: &ManagedAddress, voting_end: u64) -> ManagedVec<EsdtTokenPayment> {
+        let mut tokens = ManagedVec::new();
+        if self.stakes(staker).is_empty() {
+            return tokens;
+        }
+
+        let position = self.stakes(staker).get();
+        if position.unlock_timestamp < voting_end {
+            return tokens;
+        }
+
+        let multiplier = position.get_multiplier_at(voting_end, self.lock_multipliers().iter());
+        for payment in position.payments.iter() {
+            tokens.push(EsdtTokenPayment::new(
+                payment.token_identifier.clone(),
+                payment.token_nonce,
+                &payment.amount * multiplier / MAX_PERCENTAGE,
+            ));
+        }
+
+        tokens
+    }
+
+    #[view(getStakedVotingPower)]
+    fn get_staked_voting_power(&self, address: &ManagedAddress) -> BigUint {
+        let mut voting_power = BigUint::zero();
+        let now = self.blockchain().get_block_timestamp();
+        for payment in self.get_staked_tokens(address, now).iter() {
+            if let Some(weight) = self.get_voting_token_weight(&payment.token_identifier, payment.token_nonce) {
+                voting_power += &payment.amount * &weight;
+            }
+        }
+
+        voting_power / ONE
+    }
+}
@@ -0,0 +1,47 @@
+use multiversx_sc::types::ManagedVec;
+use multiversx_sc_scenario::api::StaticApi;
+use tfn_franchise_dao::common::staking_config::StakePosition;
+
+const MONTH: u64 = 30 * 24 * 60 * 60;
+const YEAR: u64 = 12 * MONTH;
+
+fn lock_multipliers() -> impl Iterator<Item = (u64, u64)> {
+    vec![(MONTH, 11_000), (6 * MONTH, 15_000), (YEAR, 20_000)].into_iter()
+}
+
+fn year_lock(now: u64) -> StakePosition<StaticApi> {
+    StakePosition {
+        payments: ManagedVec::new(),
+        unlock_timestamp: now + YEAR,
+        multiplier: 20_000,
+    }
+}
+
+#[test]
+fn top_up_with_shorter_lock_is_rejected() {
+    let position = year_lock(0);
+
+    assert!(!position.can_relock(MONTH));
+    assert!(!position.can_relock(YEAR - 1));
+    assert!(position.can_relock(YEAR));
+    // a month later a new year lock ends after the current one
+    assert!(position.can_relock(MONTH + YEAR));
+}
+
+#[test]
+fn multiplier_decays_as_unlock_nears() {
+    let position = year_lock(0);
+
+    assert_eq!(position.get_multiplier_at(0, lock_multipliers()), 20_000);
+    assert_eq!(position.get_multiplier_at(MONTH, lock_multipliers()), 15_000);
+    assert_eq!(position.get_multiplier_at(YEAR - MONTH, lock_multipliers()), 11_000);
+    assert_eq!(position.get_multiplier_at(YEAR - 1, lock_multipliers()), 10_000);
+}
+
+#[test]
+fn multiplier_never_exceeds_the_position_lock() {
+    let mut position = year_lock(0);
+    position.multiplier = 15_000;
+
+    assert_eq!(position.get_multiplier_at(0, lock_multipliers()), 15_000);
+}